@@ -13,37 +13,51 @@
 //!  - Receiving messages from the `AmplifierHandler`
 //!    - Amplifier state messages (`AmplifierStateMsg`) for passing on to the UI
 //!    - Amplifier connection status messages (`AmplifierHandlerConnectionStatusMsg`)
+//!  - Publishing amplifier state and connection status changes to the event bus.
 
 use std::default::Default;
+use std::time::{Instant, SystemTime};
 
 use log::Level::{Debug, Error, Info, Warn};
 use serde;
 use tauri::{AppHandle};
 use tokio::select;
 use tokio::sync::mpsc;
-use tokio::sync::mpsc::Sender;
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep_until, Duration};
 use ts_rs::TS;
 
 use amplifier_handler::{
-    AmplifierHandler, AmplifierHandlerAction, AmplifierHandlerConnectionStatus, AmplifierHandlerRxChannelMsg,
-    AmplifierHandlerTxChannelMsg, AmplifierState,
+    AmplifierHandler, AmplifierHandlerAction, AmplifierHandlerRxChannelMsg, AmplifierHandlerTxChannelMsg,
 };
 use discovery::{discover_amplifiers, AmplifierDevice};
 
 use crate::errors::PunyTunesError;
+use crate::event_bus::{AppEvent, EventBus};
 use crate::messaging::AppMessageType;
-use crate::streammagic_manager::StreamMagicManagerChannelMsg;
+use crate::reconnect_policy::{ReconnectPolicy, ReconnectPolicyConfig, ReconnectState};
+use crate::resume_detector::ResumeDetector;
 use crate::traits::CustomEmitters;
 
 mod amplifier_handler;
 mod discovery;
 mod hegel;
 
-// TODO: Consider what to do when AmplifierHandler connection is lost. Worst case, a
-//  user-requested discovery should pick up the amplifier again. It would be nice to
-//  auto-reconnect if the connection is unexpectedly lost (without ending up in a
-//  never-ending loop of attempted reconnects).
+// Published to the event bus
+pub use amplifier_handler::{AmplifierHandlerConnectionStatus, AmplifierState};
+
+// Amplifier reconnection timing, used when the AmplifierHandler connection is unexpectedly lost.
+// After a few quick attempts, an attempt is made every minute (the amplifier may have been
+// unplugged). A user-requested discovery will also pick up the amplifier again.
+const RECONNECT_POLICY: ReconnectPolicyConfig = ReconnectPolicyConfig {
+    base_delay: Duration::from_millis(1000),
+    max_delay: Duration::from_millis(4000),
+    jitter: 0.2,
+    max_fast_attempts: 4,
+    background_interval: Duration::from_secs(60),
+};
+
+// Gap between interval ticks which is assumed to mean the machine has resumed from sleep
+const RESUME_THRESHOLD: Duration = Duration::from_secs(5);
 
 /// All `AmplifierManager` state which might be of interest to the UI.
 #[derive(Clone, Debug, serde::Serialize, TS)]
@@ -52,6 +66,7 @@ pub struct AmplifierManagerStateMsg {
     is_discovering: bool,
     is_handling_amplifier: bool,
     managed_device: Option<AmplifierDevice>,
+    reconnect: ReconnectState,
 }
 
 /// Messages which can be sent to the `AmplifierManager` for the AmplifierManager to act on.
@@ -94,7 +109,7 @@ pub enum AmplifierManagerChannelMsg {
 
 pub struct AmplifierManager {
     app_handle: AppHandle,
-    streammagic_manager_channel: Sender<StreamMagicManagerChannelMsg>,
+    event_bus: EventBus,
     // Receive commands from the application or AmplifierManager itself
     rx_channel: mpsc::Receiver<AmplifierManagerChannelMsg>,
     // Send commands to itself (for use by spawned tasks such as discovery, which won't
@@ -111,21 +126,22 @@ pub struct AmplifierManager {
     is_shutting_down: bool,
     is_testing_connection: bool,
     managed_device: Option<AmplifierDevice>,
-    max_reconnect_attempts: u8,
-    reconnect_attempts: u8,
-    reconnect_delay: u64,
+    reconnect: ReconnectPolicy,
+    // When a delayed reconnection attempt is due. The attempt is made from the run loop, so the
+    // delay doesn't hold up anything else.
+    scheduled_connection: Option<Instant>,
 }
 
 impl AmplifierManager {
     pub fn new(
         app_handle: AppHandle,
-        streammagic_manager_channel: Sender<StreamMagicManagerChannelMsg>,
+        event_bus: EventBus,
         rx_channel: mpsc::Receiver<AmplifierManagerChannelMsg>,
         tx_channel: mpsc::Sender<AmplifierManagerChannelMsg>,
     ) -> AmplifierManager {
         AmplifierManager {
             app_handle,
-            streammagic_manager_channel,
+            event_bus,
             rx_channel,
             tx_channel,
 
@@ -139,9 +155,8 @@ impl AmplifierManager {
             is_shutting_down: false,
             is_testing_connection: false,
             managed_device: None,
-            max_reconnect_attempts: 3,
-            reconnect_attempts: 0,
-            reconnect_delay: 1000,
+            reconnect: ReconnectPolicy::new(RECONNECT_POLICY),
+            scheduled_connection: None,
         }
     }
 
@@ -157,6 +172,7 @@ impl AmplifierManager {
                     is_discovering: self.is_discovering,
                     is_handling_amplifier: self.is_handling_amplifier,
                     managed_device: self.managed_device.clone(),
+                    reconnect: self.reconnect.state(),
                 },
             )
             .await;
@@ -173,6 +189,7 @@ impl AmplifierManager {
     // State handling
 
     async fn set_amplifier_state(&mut self, amplifier_state: AmplifierState) {
+        self.event_bus.publish(AppEvent::AmplifierState(amplifier_state.clone()));
         self.amp_state = amplifier_state;
         self.emit_amplifier_state().await;
     }
@@ -198,7 +215,6 @@ impl AmplifierManager {
         // Will also optionally trigger a device activation.
 
         send_app_log!(
-            &self.streammagic_manager_channel,
             Info,
             "Initiating amplifier discovery"
         );
@@ -207,14 +223,12 @@ impl AmplifierManager {
         // self.clear_devices().await;
 
         let amp_channel_clone = self.tx_channel.clone();
-        let sm_channel_clone = self.streammagic_manager_channel.clone();
 
         tauri::async_runtime::spawn(async move {
-            match discover_amplifiers(&amp_channel_clone, &sm_channel_clone).await {
-                Ok(()) => send_app_log!(&sm_channel_clone, Info, "Amplifier UPnP discovery complete"),
+            match discover_amplifiers(&amp_channel_clone).await {
+                Ok(()) => send_app_log!(Info, "Amplifier UPnP discovery complete"),
                 Err(e) => {
                     send_app_log!(
-                        &sm_channel_clone,
                         Error,
                         "Amplifier discovery failed with error: {:?}",
                         e
@@ -231,7 +245,6 @@ impl AmplifierManager {
     /// Send the given AmplifierHandlerAction to the amplifier handler.
     async fn send_handler_action_to_amplifier_handler(&mut self, handler_action: AmplifierHandlerAction) {
         send_app_log!(
-            &self.streammagic_manager_channel,
             Debug,
             "Sending {:?} to AmplifierHandler",
             &handler_action
@@ -248,7 +261,6 @@ impl AmplifierManager {
                     Ok(_) => {}
                     Err(e) => {
                         send_app_log!(
-                            &self.streammagic_manager_channel,
                             Warn,
                             "Could not send {:?} to AmplifierHandler: {:?}",
                             &handler_action,
@@ -259,7 +271,6 @@ impl AmplifierManager {
             }
             None => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "No AmplifierHandler send channel found"
                 );
@@ -269,9 +280,11 @@ impl AmplifierManager {
 
     /// Stop any running amplifier handler.
     async fn stop_amplifier_handler(&mut self) {
+        // Any scheduled reconnection is for the handler being stopped
+        self.scheduled_connection = None;
+
         if let Some(handle) = self.amp_handler_join_handle.take() {
             send_app_log!(
-                &self.streammagic_manager_channel,
                 Info,
                 "Stopping existing AmplifierHandler"
             );
@@ -282,14 +295,12 @@ impl AmplifierManager {
                 Ok(amp_handler_result) => match amp_handler_result {
                     Ok(_) => {
                         send_app_log!(
-                            &self.streammagic_manager_channel,
                             Info,
                             "AmplifierHandler successfully stopped"
                         );
                     }
                     Err(e) => {
                         send_app_log!(
-                            &self.streammagic_manager_channel,
                             Warn,
                             "AmplifierHandler stopped with error: {:?}",
                             e
@@ -298,7 +309,6 @@ impl AmplifierManager {
                 },
                 Err(e) => {
                     send_app_log!(
-                        &self.streammagic_manager_channel,
                         Warn,
                         "Could not stop AmplifierHandler: {:?}",
                         e
@@ -309,7 +319,6 @@ impl AmplifierManager {
             self.amp_handler_join_handle = None;
         } else if self.handler_start_count > 0 {
             send_app_log!(
-                &self.streammagic_manager_channel,
                 Info,
                 "No existing AmplifierHandler to stop"
             );
@@ -327,7 +336,6 @@ impl AmplifierManager {
 
         if device.manufacturer != "Hegel" {
             send_app_log!(
-                &self.streammagic_manager_channel,
                 Info,
                 "Ignoring unsupported amplifier manufacturer: {}",
                 &device.manufacturer
@@ -346,22 +354,18 @@ impl AmplifierManager {
         self.amp_handler_send_channel = Some(handler_cmd_channel_tx);
         self.amp_handler_receive_channel = Some(handler_msg_channel_rx);
 
-        let streammagic_mgr_channel = self.streammagic_manager_channel.clone();
-
         // Start the AmplifierHandler and wait for it to complete
         self.amp_handler_join_handle = Some(tauri::async_runtime::spawn(async move {
             let mut amp_handler = hegel::HegelAmplifierHandler::new(
                 device,
                 handler_cmd_channel_rx,
                 handler_msg_channel_tx,
-                streammagic_mgr_channel.clone(),
             );
 
             let amp_handler_result = amp_handler.run().await;
 
             if let Err(err) = &amp_handler_result {
                 send_app_log!(
-                    &streammagic_mgr_channel,
                     Warn,
                     "AmplifierManager detected handler error: {:?}",
                     &err
@@ -375,6 +379,45 @@ impl AmplifierManager {
         self.emit_manager_state().await;
     }
 
+    /// Attempt to reconnect to the managed amplifier (if there is one), according to the
+    /// reconnection policy. Attempts are scheduled, and made from the run loop once they're due.
+    async fn reconnect_amplifier(&mut self) {
+        if self.managed_device.is_none() {
+            return;
+        }
+
+        let delay = self.reconnect.schedule_next(Instant::now());
+        self.on_manager_state_changed().await;
+
+        if self.reconnect.is_background() {
+            // The attempt will be made by the interval check once it's due
+            send_app_log!(
+                Warn,
+                "AmplifierManager will attempt an amplifier reconnect (#{}) in {}s",
+                self.reconnect.attempts(),
+                delay.as_secs(),
+            );
+
+            return;
+        }
+
+        send_app_log!(
+            Warn,
+            "AmplifierManager will attempt an amplifier reconnect (#{}) after {}ms delay",
+            self.reconnect.attempts(),
+            delay.as_millis(),
+        );
+
+        self.scheduled_connection = Some(Instant::now() + delay);
+    }
+
+    /// Ask the AmplifierHandler to test its amplifier connection.
+    async fn test_amplifier_connection(&mut self) {
+        self.is_testing_connection = true;
+        self.send_handler_action_to_amplifier_handler(AmplifierHandlerAction::TestConnection)
+            .await;
+    }
+
     // --------------------------------------------------------------------------------------------
 
     async fn initialize(&mut self) {
@@ -391,8 +434,11 @@ impl AmplifierManager {
         // Configure an interval which will always be checked regardless of whether there's any
         // items waiting in a channel for processing.
         let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut resume_detector = ResumeDetector::new(RESUME_THRESHOLD);
 
         loop {
+            let scheduled_connection_at = self.scheduled_connection;
+
             select! {
                 // --------------------------------------------------------------------------------
                 // Incoming commands requested of the Manager. These commands will be coming from
@@ -416,28 +462,25 @@ impl AmplifierManager {
                                         self.emit_amplifier_state().await;
                                     },
                                     AmplifierManagerAction::ProcessDiscoveredDevice(device) => {
-                                        send_app_log!(&self.streammagic_manager_channel, Info, "Processing discovered amplifier: {}", device);
-                                        self.reconnect_attempts = 0;
+                                        send_app_log!(Info, "Processing discovered amplifier: {}", device);
+                                        self.reconnect.reset();
                                         self.handle_amplifier(device).await;
                                     },
                                     AmplifierManagerAction::SetIsDiscovering(is_discovering) => {
                                         self.set_is_discovering(is_discovering).await;
                                     },
                                     AmplifierManagerAction::ShutDown => {
-                                        send_app_log!(&self.streammagic_manager_channel, Info, "AmplifierManager shutdown requested");
+                                        send_app_log!(Info, "AmplifierManager shutdown requested");
                                         self.is_shutting_down = true;
                                         self.stop_amplifier_handler().await;
                                         break;
                                     },
                                     AmplifierManagerAction::TestConnection => {
                                         if self.is_handling_amplifier {
-                                            self.is_testing_connection = true;
-                                            self.send_handler_action_to_amplifier_handler(
-                                                AmplifierHandlerAction::TestConnection
-                                            ).await;
+                                            self.test_amplifier_connection().await;
                                         } else {
                                             send_app_log!(
-                                                &self.streammagic_manager_channel, Warn,
+                                                Warn,
                                                 "Ignoring request to test connection (not currently handling an amplifier)"
                                             );
                                         }
@@ -451,7 +494,7 @@ impl AmplifierManager {
                                         match sender.send(AmplifierHandlerRxChannelMsg::AmplifierActionMsg(amplifier_action.clone())).await {
                                             Ok(_) => {}
                                             Err(e) => {
-                                                send_app_log!(&self.streammagic_manager_channel, Warn,
+                                                send_app_log!(Warn,
                                                     "Could not send command {:?} to AmplifierHandler: {:?}",
                                                     amplifier_action, e
                                                 );
@@ -460,7 +503,6 @@ impl AmplifierManager {
                                     }
                                 } else {
                                     send_app_log!(
-                                        &self.streammagic_manager_channel,
                                         Warn,
                                         "AmplifierManager is not handling an amplifier connection"
                                     );
@@ -482,40 +524,19 @@ impl AmplifierManager {
                                 self.set_amplifier_state(amp_state).await;
                             },
                             AmplifierHandlerTxChannelMsg::AmplifierHandlerConnectionStatusMsg(status) => {
+                                self.event_bus.publish(AppEvent::AmplifierConnectionStatus(status.clone()));
+
                                 match &status {
                                     AmplifierHandlerConnectionStatus::Connected => {
+                                        self.reconnect.reset();
                                         self.set_is_handling_amplifier(true).await;
-                                        self.reconnect_attempts = 0;
                                     },
                                     AmplifierHandlerConnectionStatus::Disconnected => {
                                         self.set_is_handling_amplifier(false).await;
                                         self.stop_amplifier_handler().await;
 
                                         if !self.is_shutting_down {
-                                            // Attempt a reconnect
-                                            if let Some(existing_device) = &self.managed_device {
-                                                self.reconnect_attempts += 1;
-
-                                                if self.reconnect_attempts <= self.max_reconnect_attempts {
-                                                    send_app_log!(
-                                                        &self.streammagic_manager_channel,
-                                                        Warn,
-                                                        "AmplifierManager is attempting an amplifier reconnect (#{})",
-                                                        self.reconnect_attempts,
-                                                    );
-
-                                                    sleep(Duration::from_millis(self.reconnect_delay)).await;
-                                                    self.handle_amplifier(existing_device.clone()).await;
-                                                } else {
-                                                    send_app_log!(
-                                                        &self.streammagic_manager_channel,
-                                                        Warn,
-                                                        "AmplifierManager is giving up on amplifier reconnects",
-                                                    );
-
-                                                    self.reconnect_attempts = 0;
-                                                }
-                                            }
+                                            self.reconnect_amplifier().await;
                                         }
                                     }
                                 }
@@ -524,15 +545,52 @@ impl AmplifierManager {
                     }
                 }
 
+                // --------------------------------------------------------------------------------
+                // A delayed reconnection attempt is due.
+                _ = sleep_until(scheduled_connection_at.unwrap_or_else(Instant::now).into()),
+                    if scheduled_connection_at.is_some() => {
+                    self.scheduled_connection = None;
+
+                    if let Some(existing_device) = self.managed_device.clone() {
+                        self.handle_amplifier(existing_device).await;
+                    }
+                },
+
                 // --------------------------------------------------------------------------------
                 // Do some checks every interval, regardless of incoming messages.
                 _ = interval.tick() => {
+                    let now = Instant::now();
+
+                    // The connection may not have survived the machine sleeping, so test it now
+                    // rather than waiting for the AmplifierHandler to notice.
+                    if let Some(gap) = resume_detector.tick(now, SystemTime::now()) {
+                        if self.is_handling_amplifier {
+                            send_app_log!(
+                                Info,
+                                "Resumed after {}s; testing amplifier connection",
+                                gap.as_secs(),
+                            );
+                            self.test_amplifier_connection().await;
+                        }
+                    }
+
+                    if self.reconnect.take_due_background_attempt(now) {
+                        if let Some(existing_device) = self.managed_device.clone() {
+                            send_app_log!(
+                                Info,
+                                "AmplifierManager is attempting a background amplifier reconnect (#{})",
+                                self.reconnect.attempts(),
+                            );
+
+                            self.handle_amplifier(existing_device).await;
+                        }
+                    }
                 },
             }
         }
 
         // We don't expect to get here unless the application is ending.
-        send_app_log!(&self.streammagic_manager_channel, Info, "AmplifierManager has ended");
+        send_app_log!(Info, "AmplifierManager has ended");
 
         Ok(())
     }
@@ -17,7 +17,6 @@ use ts_rs::TS;
 use crate::amplifier_manager::discovery::AmplifierDevice;
 use crate::amplifier_manager::AmplifierAction;
 use crate::errors::PunyTunesError;
-use crate::streammagic_manager::StreamMagicManagerChannelMsg;
 
 #[derive(Clone, Debug, serde::Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/AmplifierState.ts")]
@@ -72,7 +71,6 @@ pub trait AmplifierHandler {
         device: AmplifierDevice,
         rx_channel: Receiver<AmplifierHandlerRxChannelMsg>,
         tx_channel: Sender<AmplifierHandlerTxChannelMsg>,
-        streammagic_manager_channel: Sender<StreamMagicManagerChannelMsg>,
     ) -> Self;
 
     async fn run(&mut self) -> Result<(), PunyTunesError>;
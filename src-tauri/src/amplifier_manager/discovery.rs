@@ -16,7 +16,6 @@ use tokio::sync::mpsc;
 use ts_rs::TS;
 
 use crate::amplifier_manager::{AmplifierManagerAction, AmplifierManagerChannelMsg};
-use crate::streammagic_manager::StreamMagicManagerChannelMsg;
 
 const MEDIA_RENDERER: URN = URN::device("schemas-upnp-org", "MediaRenderer", 1);
 
@@ -40,16 +39,13 @@ impl fmt::Display for AmplifierDevice {
 
 pub async fn discover_amplifiers(
     amplifier_manager_channel: &mpsc::Sender<AmplifierManagerChannelMsg>,
-    streammagic_manager_channel: &mpsc::Sender<StreamMagicManagerChannelMsg>,
 ) -> Result<(), rupnp::Error> {
     let amp_mgr = amplifier_manager_channel.clone();
-    let streammagic_mgr = streammagic_manager_channel.clone();
     let search_target = SearchTarget::URN(MEDIA_RENDERER);
     let mut seen_udns: HashSet<String> = HashSet::new();
     let mut found_count = 0;
 
     send_app_log!(
-        streammagic_mgr,
         Info,
         "Performing amplifier UPnP discovery (MediaRenderer only)"
     );
@@ -81,7 +77,6 @@ pub async fn discover_amplifiers(
                     };
 
                     send_app_log!(
-                        streammagic_mgr,
                         Info,
                         "Amplifier device discovered: {} @ {}",
                         &amplifier_device,
@@ -104,7 +99,6 @@ pub async fn discover_amplifiers(
                     }
                 } else {
                     send_app_log!(
-                        streammagic_mgr,
                         Info,
                         "Amplifier UPnP discovery is ignoring MediaRenderer device '{}' ({}) from {}",
                         device.friendly_name(),
@@ -115,12 +109,11 @@ pub async fn discover_amplifiers(
             }
         }
         Err(e) => {
-            send_app_log!(streammagic_mgr, Error, "Amplifier UPnP discovery error: {:?}", e);
+            send_app_log!(Error, "Amplifier UPnP discovery error: {:?}", e);
         }
     }
 
     send_app_log!(
-        streammagic_mgr,
         Info,
         "Amplifier UPnP discovery found {} amplifier{}",
         &found_count,
@@ -5,7 +5,7 @@
 
 use std::default::Default;
 use std::io::Cursor;
-use std::time::SystemTime;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use bytes::{Buf, BytesMut};
@@ -29,7 +29,6 @@ use crate::amplifier_manager::amplifier_handler::{
 use crate::amplifier_manager::discovery::AmplifierDevice;
 use crate::amplifier_manager::AmplifierAction;
 use crate::errors::PunyTunesError;
-use crate::streammagic_manager::StreamMagicManagerChannelMsg;
 use crate::utils::host_from_url;
 
 // ================================================================================================
@@ -359,11 +358,10 @@ pub struct HegelAmplifierHandler {
     device: AmplifierDevice,
     rx_channel: Receiver<AmplifierHandlerRxChannelMsg>,
     tx_channel: Sender<AmplifierHandlerTxChannelMsg>,
-    streammagic_manager_channel: Sender<StreamMagicManagerChannelMsg>,
 
     amplifier_state: AmplifierState,
     connection_status: AmplifierHandlerConnectionStatus,
-    connection_test_start_time: Option<SystemTime>,
+    connection_test_start_time: Option<Instant>,
     connection_test_timeout: u128,
     connection_timeout: u64,
     max_heartbeat_timeout: u128,
@@ -375,13 +373,11 @@ impl AmplifierHandler for HegelAmplifierHandler {
         device: AmplifierDevice,
         rx_channel: Receiver<AmplifierHandlerRxChannelMsg>,
         tx_channel: Sender<AmplifierHandlerTxChannelMsg>,
-        streammagic_manager_channel: Sender<StreamMagicManagerChannelMsg>,
     ) -> Self {
         HegelAmplifierHandler {
             device,
             rx_channel,
             tx_channel,
-            streammagic_manager_channel,
 
             amplifier_state: Default::default(),
             connection_status: Disconnected,
@@ -393,14 +389,13 @@ impl AmplifierHandler for HegelAmplifierHandler {
     }
 
     async fn run(&mut self) -> Result<(), PunyTunesError> {
-        send_app_log!(&self.streammagic_manager_channel, Info, "AmplifierHandler is running");
+        send_app_log!(Info, "AmplifierHandler is running");
 
         // Attempt to connect to the amplifier
         let stream = match self.connect_to_amplifier().await {
             Ok(stream) => stream,
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Info,
                     "AmplifierHandler stopping after failed connection attempt"
                 );
@@ -411,7 +406,6 @@ impl AmplifierHandler for HegelAmplifierHandler {
         };
 
         send_app_log!(
-            &self.streammagic_manager_channel,
             Info,
             "Connected to amplifier: {}",
             &self.device.friendly_name
@@ -424,7 +418,9 @@ impl AmplifierHandler for HegelAmplifierHandler {
         // Configure an interval which will always be checked regardless of whether there's any
         // items waiting in a channel for processing.
         let mut interval = tokio::time::interval(Duration::from_millis(500));
-        let mut last_amplifier_heartbeat = SystemTime::now();
+        // Heartbeat and test timings use the monotonic clock, so they're unaffected by wall clock
+        // changes (e.g. NTP syncs).
+        let mut last_amplifier_heartbeat = Instant::now();
 
         // Run forever (or until told to stop), processing messages from the Manager and from the
         // amplifier. Also perform interval checks (heartbeat, etc).
@@ -439,7 +435,7 @@ impl AmplifierHandler for HegelAmplifierHandler {
                             AmplifierHandlerRxChannelMsg::AmplifierHandlerActionMsg(handler_action) => {
                                 match handler_action {
                                     AmplifierHandlerAction::ShutDown => {
-                                        send_app_log!(&self.streammagic_manager_channel, Info, "AmplifierHandler is shutting down");
+                                        send_app_log!(Info, "AmplifierHandler is shutting down");
                                         self.set_connection_status(Disconnected).await;
 
                                         // This breaks out of run() which will close the connection
@@ -454,6 +450,14 @@ impl AmplifierHandler for HegelAmplifierHandler {
                                 self.send_action_to_amplifier(&mut hegel_connection, action).await;
                             },
                         }
+                    } else {
+                        // The manager has dropped its end of the channel (e.g. it's being restarted)
+                        send_app_log!(
+                            Warn,
+                            "AmplifierHandler command channel closed; shutting down"
+                        );
+
+                        break;
                     }
                 },
 
@@ -470,7 +474,6 @@ impl AmplifierHandler for HegelAmplifierHandler {
                                     Ok(cmd) => match cmd {
                                         HegelCommand::Error(e) => {
                                             send_app_log!(
-                                                &self.streammagic_manager_channel,
                                                 Warn,
                                                 "AmplifierHandler received error from amplifier: {e}",
                                             );
@@ -488,11 +491,11 @@ impl AmplifierHandler for HegelAmplifierHandler {
                                             if let Some(_) = self.connection_test_start_time {
                                                 self.connection_test_start_time = None;
                                                 send_app_log!(
-                                                    &self.streammagic_manager_channel, Info, "Amplifier connection OK"
+                                                    Info, "Amplifier connection OK"
                                                 );
                                             }
 
-                                            last_amplifier_heartbeat = SystemTime::now();
+                                            last_amplifier_heartbeat = Instant::now();
 
                                             match self.amplifier_state.is_powered_on {
                                                 Some(handler_power) => {
@@ -520,7 +523,6 @@ impl AmplifierHandler for HegelAmplifierHandler {
                                     },
                                     Err(e) => {
                                         send_app_log!(
-                                            &self.streammagic_manager_channel,
                                             Warn,
                                             "AmplifierHandler did not understand amplifier frame: {:?} : {}",
                                             frame,
@@ -531,7 +533,6 @@ impl AmplifierHandler for HegelAmplifierHandler {
                             },
                             None => {
                                 send_app_log!(
-                                    &self.streammagic_manager_channel,
                                     Info,
                                     "AmplifierHandler connection cleanly closed by amplifier"
                                 );
@@ -541,7 +542,7 @@ impl AmplifierHandler for HegelAmplifierHandler {
                         },
                         Err(e) => {
                             send_app_log!(
-                                &self.streammagic_manager_channel, Warn, "AmplifierHandler connection error: {:?}", e
+                                Warn, "AmplifierHandler connection error: {:?}", e
                             );
                             self.set_connection_status(Disconnected).await;
 
@@ -556,43 +557,30 @@ impl AmplifierHandler for HegelAmplifierHandler {
                 _ = interval.tick() => {
                     // If we're in test mode, check if we haven't received a test response in time
                     if let Some(test_start) = self.connection_test_start_time {
-                        match SystemTime::now().duration_since(test_start) {
-                            Ok(test_duration) => {
-                                if test_duration.as_millis() > self.connection_test_timeout {
-                                    self.connection_test_start_time = None;
-                                    send_app_log!(&self.streammagic_manager_channel, Warn, "AmplifierHandler connection test failed");
+                        if test_start.elapsed().as_millis() > self.connection_test_timeout {
+                            self.connection_test_start_time = None;
+                            send_app_log!(Warn, "AmplifierHandler connection test failed");
 
-                                    hegel_connection.shutdown().await;
-                                    self.set_connection_status(Disconnected).await;
+                            hegel_connection.shutdown().await;
+                            self.set_connection_status(Disconnected).await;
 
-                                    break;
-                                }
-                            }
-                            _ => {},
-                        };
+                            break;
+                        }
                     }
 
                     // Check the amplifier heartbeat
-                    let now = SystemTime::now();
+                    if last_amplifier_heartbeat.elapsed().as_millis() > self.max_heartbeat_timeout {
+                        send_app_log!(
+                            Warn,
+                            "AmplifierHandler hasn't received amplifier heartbeat for {}ms; assuming connection lost",
+                            self.max_heartbeat_timeout
+                        );
 
-                    match now.duration_since(last_amplifier_heartbeat) {
-                        Ok(duration) => {
-                            if duration.as_millis() > self.max_heartbeat_timeout {
-                                send_app_log!(
-                                    &self.streammagic_manager_channel,
-                                    Warn,
-                                    "AmplifierHandler hasn't received amplifier heartbeat for {}ms; assuming connection lost",
-                                    self.max_heartbeat_timeout
-                                );
+                        hegel_connection.shutdown().await;
+                        self.set_connection_status(Disconnected).await;
 
-                                hegel_connection.shutdown().await;
-                                self.set_connection_status(Disconnected).await;
-
-                                break;
-                            }
-                        }
-                        _ => {},
-                    };
+                        break;
+                    }
 
                     // Check whether any state needs to be requested. Ideally this will never be
                     // the case, but the amplifier sometimes won't response to state requests
@@ -604,7 +592,7 @@ impl AmplifierHandler for HegelAmplifierHandler {
             }
         }
 
-        send_app_log!(&self.streammagic_manager_channel, Info, "AmplifierHandler has stopped");
+        send_app_log!(Info, "AmplifierHandler has stopped");
 
         Ok(())
     }
@@ -624,7 +612,6 @@ impl HegelAmplifierHandler {
             Ok(()) => {}
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "Could not send AmplifierHandler connection status: {:?}",
                     e
@@ -644,7 +631,6 @@ impl HegelAmplifierHandler {
             Ok(()) => {}
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "Could not send AmplifierHandler amplifier state: {:?}",
                     e
@@ -684,7 +670,6 @@ impl HegelAmplifierHandler {
             Ok(_) => {}
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "Could not send {:?} command: {e}",
                     &command
@@ -743,7 +728,6 @@ impl HegelAmplifierHandler {
             Ok(hegel_frame) => {
                 if let Err(e) = connection.write_frame(&hegel_frame).await {
                     send_app_log!(
-                        &self.streammagic_manager_channel,
                         Warn,
                         "Could not send amplifier frame '{:?}' for {:?} action to amplifier: {e}",
                         &hegel_frame,
@@ -753,7 +737,6 @@ impl HegelAmplifierHandler {
             }
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "Could not generate amplifier command: {e}"
                 );
@@ -771,11 +754,10 @@ impl HegelAmplifierHandler {
             .await
         {
             Ok(_) => {
-                self.connection_test_start_time = Some(SystemTime::now());
+                self.connection_test_start_time = Some(Instant::now());
             }
             Err(e) => {
                 send_app_log!(
-                    &self.streammagic_manager_channel,
                     Warn,
                     "Could not initiate amplifier connection test: {:?}",
                     e
@@ -51,13 +51,7 @@ pub async fn discover_amplifier(
 }
 
 #[tauri::command]
-pub async fn emit_app_log(
-    stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>,
-    level: String,
-    message: String,
-) -> Result<(), ()> {
-    // TODO: Figure out how to deserialize a SerializableLevel; or just rethink logging
-    //  across the app...
+pub async fn emit_app_log(level: String, message: String) -> Result<(), ()> {
     let log_level = match level.as_str() {
         "debug" => log::Level::Debug,
         "error" => log::Level::Error,
@@ -67,19 +61,18 @@ pub async fn emit_app_log(
         _ => log::Level::Info,
     };
 
-    send_app_log!(stream_magic_manager_channel.0, log_level, "{}", message);
+    send_app_log!(log_level, "{}", message);
 
     Ok(())
 }
 
 #[tauri::command]
 pub async fn send_amplifier_action(
-    stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>,
     amplifier_manager_channel: tauri::State<'_, AmplifierManagerChannel>,
     action: AmplifierAction,
 ) -> Result<(), ()> {
     send_app_log!(
-        stream_magic_manager_channel.0, log::Level::Info, "Amplifier action: {:?}", &action
+        log::Level::Info, "Amplifier action: {:?}", &action
     );
 
     match amplifier_manager_channel
@@ -103,7 +96,7 @@ pub async fn send_streamer_action(
     action: StreamerAction,
 ) -> Result<(), ()> {
     send_app_log!(
-        stream_magic_manager_channel.0, log::Level::Info, "Streamer action: {:?}", &action
+        log::Level::Info, "Streamer action: {:?}", &action
     );
 
     match stream_magic_manager_channel
@@ -122,15 +115,10 @@ pub async fn send_streamer_action(
 }
 
 #[tauri::command]
-pub async fn shutdown(stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>) -> Result<(), ()> {
-    send_app_log!(stream_magic_manager_channel.0, log::Level::Info, "PunyTunes shutdown requested");
+pub async fn shutdown(app_handle: tauri::AppHandle) -> Result<(), ()> {
+    send_app_log!(log::Level::Info, "PunyTunes shutdown requested");
 
-    // TODO: Add amplifier shutdown
-
-    send_manager_action!(
-        stream_magic_manager_channel.0,
-        StreamMagicManagerAction::ShutDown
-    );
+    crate::shutdown::shutdown(&app_handle).await;
 
     Ok(())
 }
@@ -140,7 +128,7 @@ pub async fn stop_websocket_client(
     stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>,
     delete_from_persisted_state: bool,
 ) -> Result<(), ()> {
-    send_app_log!(stream_magic_manager_channel.0, log::Level::Info, "Stopping WebSocket client");
+    send_app_log!(log::Level::Info, "Stopping WebSocket client");
 
     send_manager_action!(
         stream_magic_manager_channel.0,
@@ -152,10 +140,9 @@ pub async fn stop_websocket_client(
 
 #[tauri::command]
 pub async fn test_amplifier_connection(
-    stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>,
     amplifier_manager_channel: tauri::State<'_, AmplifierManagerChannel>,
 ) -> Result<(), ()> {
-    send_app_log!(stream_magic_manager_channel.0, log::Level::Info, "Testing amplifier connection");
+    send_app_log!(log::Level::Info, "Testing amplifier connection");
 
     send_amplifier_manager_action!(
         amplifier_manager_channel.0,
@@ -167,7 +154,7 @@ pub async fn test_amplifier_connection(
 
 #[tauri::command]
 pub async fn test_streamer_connection(stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>) -> Result<(), ()> {
-    send_app_log!(stream_magic_manager_channel.0, log::Level::Info, "Testing streamer connection");
+    send_app_log!(log::Level::Info, "Testing streamer connection");
 
     send_manager_action!(
         stream_magic_manager_channel.0,
@@ -182,7 +169,9 @@ pub async fn ui_ready(
     stream_magic_manager_channel: tauri::State<'_, StreamMagicManagerChannel>,
     amplifier_manager_channel: tauri::State<'_, AmplifierManagerChannel>,
 ) -> Result<(), ()> {
-    send_app_log!(stream_magic_manager_channel.0, log::Level::Info, "UI ready");
+    send_app_log!(log::Level::Info, "UI ready");
+
+    crate::logging::on_ui_ready().await;
 
     send_manager_action!(
         stream_magic_manager_channel.0,
@@ -0,0 +1,110 @@
+//! An internal event bus, so parts of the backend can observe each other's state.
+//!
+//! The managers publish their state changes (Streamer zone state, play state, and power; amplifier
+//! state; and connection status for both) to the bus, as well as emitting them to the UI. Anything
+//! interested in those changes can subscribe to the bus, rather than needing its own channel to
+//! (or from) each manager.
+//!
+//! The bus is a `tokio::sync::broadcast` channel, so every subscriber receives every event.
+//! Publishing never blocks; a subscriber which falls too far behind misses the oldest events (and
+//! is told how many it missed when it next receives).
+
+use tokio::sync::broadcast;
+
+use crate::amplifier_manager::{AmplifierHandlerConnectionStatus, AmplifierState};
+use crate::streammagic_manager::{ConnectionState, StreamerSystemPower, StreamerZonePlayState, StreamerZoneState};
+
+// Number of events retained for subscribers which haven't received them yet
+pub const EVENT_BUS_CAPACITY: usize = 256;
+
+/// State changes published by the managers.
+#[derive(Clone, Debug)]
+pub enum AppEvent {
+    AmplifierConnectionStatus(AmplifierHandlerConnectionStatus),
+    AmplifierState(AmplifierState),
+    StreamerConnectionState(ConnectionState),
+    StreamerSystemPower(StreamerSystemPower),
+    // Boxed, as play state (with its track metadata) is much larger than the other events
+    StreamerZonePlayState(Box<StreamerZonePlayState>),
+    StreamerZoneState(StreamerZoneState),
+}
+
+/// A handle to the event bus. Handles are cheap to clone, and all clones publish to (and
+/// subscribe to) the same bus.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+
+        EventBus { sender }
+    }
+
+    /// Publish an event to all current subscribers.
+    pub fn publish(&self, event: AppEvent) {
+        // Sending only fails when there are no subscribers, in which case nobody needs the event
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribe to all events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.sender.subscribe()
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    use super::*;
+
+    #[test]
+    fn test_publish_without_subscribers() {
+        let event_bus = EventBus::new(4);
+
+        event_bus.publish(AppEvent::AmplifierState(AmplifierState::default()));
+
+        // Subscribers only receive events published after they subscribed
+        let mut receiver = event_bus.subscribe();
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn test_every_subscriber_receives_events() {
+        let event_bus = EventBus::new(4);
+        let mut first = event_bus.clone().subscribe();
+        let mut second = event_bus.subscribe();
+
+        event_bus.publish(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Connected));
+
+        for receiver in [&mut first, &mut second] {
+            assert!(matches!(
+                receiver.try_recv(),
+                Ok(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Connected))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_lagging_subscriber_misses_oldest_events() {
+        let event_bus = EventBus::new(2);
+        let mut receiver = event_bus.subscribe();
+
+        event_bus.publish(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Connected));
+        event_bus.publish(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Disconnected));
+        event_bus.publish(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Connected));
+
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Lagged(1))));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(AppEvent::AmplifierConnectionStatus(AmplifierHandlerConnectionStatus::Disconnected))
+        ));
+    }
+}
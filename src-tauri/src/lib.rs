@@ -5,9 +5,15 @@ pub mod amplifier_manager;
 pub mod average;
 pub mod commands;
 pub mod errors;
+pub mod event_bus;
+pub mod logging;
 pub mod messaging;
 pub mod persisted_state;
+pub mod reconnect_policy;
+pub mod resume_detector;
+pub mod shutdown;
 pub mod state;
 pub mod streammagic_manager;
+pub mod supervisor;
 pub mod traits;
 pub mod utils;
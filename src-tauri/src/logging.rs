@@ -0,0 +1,110 @@
+//! The application logging pipeline.
+//!
+//! Application logs (see `send_app_log!`) are written to the Rust log straight away, and are also
+//! emitted to the UI as `AppLog` messages. Emitting is handled by a dedicated task which receives
+//! logs on its own channel, so logging never competes with manager commands, and logs are emitted
+//! in the order they were sent. Logs sent before the UI is ready are buffered (up to a limit), and
+//! are emitted once the UI says it's ready.
+//!
+//! Sending a log never blocks, and never spawns a task. If the channel is full then the log is
+//! still written to the Rust log, but it isn't emitted to the UI. The number of logs which the UI
+//! missed out on is reported to the UI instead.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use log::warn;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+use crate::messaging::{AppLog, AppMessageType};
+use crate::traits::CustomEmitters;
+
+// Number of logs which can be waiting to be emitted to the UI
+const LOG_CHANNEL_SIZE: usize = 1024;
+
+// Number of logs retained for the UI until it's ready. Older logs are dropped first.
+const PRE_UI_BUFFER_SIZE: usize = 500;
+
+static LOG_CHANNEL: OnceLock<mpsc::Sender<LogPipelineMsg>> = OnceLock::new();
+
+// Logs which couldn't be emitted to the UI since this was last reported
+static DROPPED_LOG_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+enum LogPipelineMsg {
+    Log(AppLog),
+    UIReady,
+}
+
+/// Start the logging pipeline. Until this is called, logs are only written to the Rust log.
+pub fn start_log_pipeline(app_handle: AppHandle) {
+    let (tx_channel, rx_channel) = mpsc::channel(LOG_CHANNEL_SIZE);
+
+    if LOG_CHANNEL.set(tx_channel).is_err() {
+        warn!("Logging pipeline has already been started");
+        return;
+    }
+
+    tauri::async_runtime::spawn(run_log_pipeline(app_handle, rx_channel));
+}
+
+/// Tell the logging pipeline that the UI is ready, so buffered logs can be emitted.
+pub async fn on_ui_ready() {
+    if let Some(channel) = LOG_CHANNEL.get() {
+        if let Err(e) = channel.send(LogPipelineMsg::UIReady).await {
+            warn!("Could not tell the logging pipeline that the UI is ready: {:?}", e);
+        }
+    }
+}
+
+/// Write a log to the Rust log, and send it to the UI. This is what `send_app_log!` calls.
+pub fn send_app_log_impl(target: &str, level: log::Level, message: String) {
+    log::log!(target: target, level, "{}", message);
+
+    if let Some(channel) = LOG_CHANNEL.get() {
+        if channel.try_send(LogPipelineMsg::Log(AppLog::new(level, &message))).is_err() {
+            DROPPED_LOG_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+async fn run_log_pipeline(app_handle: AppHandle, mut rx_channel: mpsc::Receiver<LogPipelineMsg>) {
+    let mut is_ui_ready = false;
+    let mut pre_ui_buffer: VecDeque<AppLog> = VecDeque::new();
+
+    while let Some(msg) = rx_channel.recv().await {
+        match msg {
+            LogPipelineMsg::Log(app_log) => {
+                if is_ui_ready {
+                    app_handle.emit_app_message(AppMessageType::AppLog, app_log).await;
+                } else {
+                    if pre_ui_buffer.len() >= PRE_UI_BUFFER_SIZE {
+                        pre_ui_buffer.pop_front();
+                        DROPPED_LOG_COUNT.fetch_add(1, Ordering::Relaxed);
+                    }
+
+                    pre_ui_buffer.push_back(app_log);
+                }
+            }
+            LogPipelineMsg::UIReady => {
+                is_ui_ready = true;
+
+                for app_log in pre_ui_buffer.drain(..) {
+                    app_handle.emit_app_message(AppMessageType::AppLog, app_log).await;
+                }
+            }
+        }
+
+        if is_ui_ready {
+            let dropped_count = DROPPED_LOG_COUNT.swap(0, Ordering::Relaxed);
+
+            if dropped_count > 0 {
+                let message = format!("{dropped_count} log messages could not be shown here (see the log file)");
+                app_handle
+                    .emit_app_message(AppMessageType::AppLog, AppLog::new(log::Level::Warn, &message))
+                    .await;
+            }
+        }
+    }
+}
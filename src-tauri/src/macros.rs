@@ -4,40 +4,41 @@ use tokio::sync::mpsc;
 use crate::amplifier_manager::{AmplifierManagerAction, AmplifierManagerChannelMsg};
 use crate::streammagic_manager::{StreamMagicManagerAction, StreamMagicManagerChannelMsg};
 
-/// The app_info, app_warn, and app_error macros assume they're being called from within a struct
-/// implementation function, where the struct has an `app_handle` field which is a Tauri
-/// `AppHandle`.
+/// send_app_log sends a log to the application logging pipeline (see logging.rs), which writes it
+/// to the Rust log and emits it to the UI. The log's target is the calling module.
 #[macro_export]
-macro_rules! app_info {
-    ($self:ident, $($arg:tt)*) => {
-        let formatted_str = format!($($arg)*);
-        let target = module_path!();
+macro_rules! send_app_log {
+    ($level:expr, $($arg:tt)*) => {
+        $crate::logging::send_app_log_impl(module_path!(), $level, format!($($arg)*))
+    };
+}
 
-        $self.app_handle.emit_app_log_with_target(target, log::Level::Info, formatted_str.as_str()).await;
+/// The app_info, app_warn, and app_error macros are shorthand for send_app_log!() at the given
+/// level.
+#[macro_export]
+macro_rules! app_info {
+    ($($arg:tt)*) => {
+        $crate::send_app_log!(log::Level::Info, $($arg)*)
     };
 }
 
 #[macro_export]
 macro_rules! app_warn {
-    ($self:ident, $($arg:tt)*) => {
-        let formatted_str = format!($($arg)*);
-        let target = module_path!();
-
-        $self.app_handle.emit_app_log_with_target(target, log::Level::Warn, formatted_str.as_str()).await;
+    ($($arg:tt)*) => {
+        $crate::send_app_log!(log::Level::Warn, $($arg)*)
     };
 }
 
 #[macro_export]
 macro_rules! app_error {
-    ($self:ident, $($arg:tt)*) => {
-        let formatted_str = format!($($arg)*);
-        let target = module_path!();
-
-        $self.app_handle.emit_app_log_with_target(target, log::Level::Error, formatted_str.as_str()).await;
+    ($($arg:tt)*) => {
+        $crate::send_app_log!(log::Level::Error, $($arg)*)
     };
 }
 
-/// send_manager_action sends a StreamMagicManagerActionMsg to the given sender.
+/// send_manager_action sends a StreamMagicManagerActionMsg to the given sender. The send is
+/// awaited (rather than spawned), so actions sent one after another arrive in the order they were
+/// sent. It can only be used in an async context.
 
 pub async fn send_manager_action_impl(
     sender: &mpsc::Sender<StreamMagicManagerChannelMsg>,
@@ -54,18 +55,34 @@ pub async fn send_manager_action_impl(
 #[macro_export]
 macro_rules! send_manager_action {
     ($sender:expr, $action:expr) => {
-        {
-            let sender = $sender.clone();
-            let action = $action;
+        $crate::macros::send_manager_action_impl(&$sender, $action).await
+    };
+}
+
+/// try_send_manager_action sends a StreamMagicManagerActionMsg to the given sender without
+/// waiting, dropping (and logging) the action if the channel is full. This is for tasks which the
+/// manager itself waits on, where waiting for room in the manager's channel could deadlock.
+pub fn try_send_manager_action_impl(
+    sender: &mpsc::Sender<StreamMagicManagerChannelMsg>,
+    action: StreamMagicManagerAction,
+) {
+    match sender.try_send(StreamMagicManagerChannelMsg::StreamMagicManagerActionMsg(action.clone()))
+    {
+        Ok(_) => {}
+        Err(e) =>
+            warn!("Dropped manager action {:?}: {:?}", &action, e),
+    }
+}
 
-            tokio::spawn(async move {
-                crate::macros::send_manager_action_impl(&sender, action).await;
-            });
-        }
+#[macro_export]
+macro_rules! try_send_manager_action {
+    ($sender:expr, $action:expr) => {
+        $crate::macros::try_send_manager_action_impl(&$sender, $action)
     };
 }
 
-/// send_amplifier_manager_action sends a AmplifierManagerActionMsg to the given sender.
+/// send_amplifier_manager_action sends a AmplifierManagerActionMsg to the given sender. As with
+/// send_manager_action, the send is awaited so that actions arrive in order.
 
 pub async fn send_amplifier_manager_action_impl(
     sender: &mpsc::Sender<AmplifierManagerChannelMsg>,
@@ -82,34 +99,6 @@ pub async fn send_amplifier_manager_action_impl(
 #[macro_export]
 macro_rules! send_amplifier_manager_action {
     ($sender:expr, $action:expr) => {
-        {
-            let sender = $sender.clone();
-            let action = $action;
-
-            tokio::spawn(async move {
-                crate::macros::send_amplifier_manager_action_impl(&sender, action).await;
-            });
-        }
-    };
-}
-
-/// send_app_log sends a StreamMagicManagerAction::EmitAppLog to the given sender.
-#[macro_export]
-macro_rules! send_app_log {
-    ($sender:expr, $level:expr, $($arg:tt)*) => {
-        {
-            let sender = $sender.clone();
-            let level = $level;
-            let formatted_str = format!($($arg)*);
-
-            tokio::spawn(async move {
-                crate::macros::send_manager_action_impl(
-                    &sender,
-                    crate::streammagic_manager::StreamMagicManagerAction::EmitAppLog(
-                        crate::messaging::AppLog::new(level, formatted_str.as_str())
-                    ),
-                ).await;
-            });
-        }
+        $crate::macros::send_amplifier_manager_action_impl(&$sender, $action).await
     };
 }
@@ -17,9 +17,12 @@ use tokio::sync::mpsc;
 
 use punytunes::amplifier_manager::{AmplifierManager, AmplifierManagerChannel};
 use punytunes::commands;
+use punytunes::event_bus::{EventBus, EVENT_BUS_CAPACITY};
 use punytunes::persisted_state::BackendState;
+use punytunes::shutdown::{self, ManagerTasks};
 use punytunes::state::PersistedBackendState;
 use punytunes::streammagic_manager::{StreamMagicManager, StreamMagicManagerChannel};
+use punytunes::supervisor::supervise;
 
 // #[cfg(debug_assertions)]
 // const LOG_TARGETS: [LogTarget; 3] = [LogTarget::Stdout, LogTarget::Webview, LogTarget::LogDir];
@@ -28,7 +31,6 @@ const LOG_TARGETS: [LogTarget; 2] = [LogTarget::Stdout, LogTarget::LogDir];
 fn main() {
     let (streammagic_manager_channel_tx, streammagic_manager_channel_rx) = mpsc::channel(32);
     let streammagic_manager_channel_tx_clone_sm = streammagic_manager_channel_tx.clone();
-    let streammagic_manager_channel_tx_clone_amp = streammagic_manager_channel_tx.clone();
 
     let (amplifier_manager_channel_tx, amplifier_manager_channel_rx) = mpsc::channel(32);
     let amplifier_manager_channel_tx_clone = amplifier_manager_channel_tx.clone();
@@ -51,6 +53,9 @@ fn main() {
 
     tauri::Builder::default()
         .setup(move |app| {
+            // Start the application logging pipeline before anything else wants to log
+            punytunes::logging::start_log_pipeline(app.app_handle());
+
             // Configure persisted state
             let app_handle = app.app_handle();
             info!(
@@ -65,38 +70,50 @@ fn main() {
             let app_handle_manager_amp = app.app_handle();
             app.manage(persisted_backend_state);
 
-            // Start the StreamMagicManager
-            tauri::async_runtime::spawn(async move {
-                let mut streammagic_manager = StreamMagicManager::new(
-                    app_handle_manager_sm,
-                    streammagic_manager_channel_rx,
-                    streammagic_manager_channel_tx_clone_sm,
-                );
-
-                // TODO: Improve Ok/Err handling; in theory we shouldn't get past run() as the
-                // manager is supposed to run forever.
-                match streammagic_manager.run().await {
-                    Ok(()) => info!("StreamMagicManager has ended"),
-                    Err(e) => info!("StreamMagicManager has ended with error: {:?}", e),
-                };
-            });
+            // The managers publish their state changes to the event bus. It's also managed by
+            // Tauri, so anything with access to the app can subscribe.
+            let event_bus = EventBus::new(EVENT_BUS_CAPACITY);
+            let event_bus_sm = event_bus.clone();
+            let event_bus_amp = event_bus.clone();
+            app.manage(event_bus);
+
+            // Start the StreamMagicManager. The managers are supposed to run forever, so they're
+            // supervised (and restarted if they end).
+            let streammagic_manager_task = tauri::async_runtime::spawn(supervise(
+                "StreamMagicManager",
+                app.app_handle(),
+                streammagic_manager_channel_rx,
+                move |manager_rx| {
+                    let mut streammagic_manager = StreamMagicManager::new(
+                        app_handle_manager_sm.clone(),
+                        event_bus_sm.clone(),
+                        manager_rx,
+                        streammagic_manager_channel_tx_clone_sm.clone(),
+                    );
+
+                    async move { streammagic_manager.run().await }
+                },
+            ));
 
             // Start the AmplifierManager
-            tauri::async_runtime::spawn(async move {
-                let mut amplifier_manager = AmplifierManager::new(
-                    app_handle_manager_amp,
-                    streammagic_manager_channel_tx_clone_amp,
-                    amplifier_manager_channel_rx,
-                    amplifier_manager_channel_tx_clone,
-                );
-
-                // TODO: Improve Ok/Err handling; in theory we shouldn't get past run() as the
-                // manager is supposed to run forever.
-                match amplifier_manager.run().await {
-                    Ok(()) => info!("AmplifierManager has ended"),
-                    Err(e) => info!("AmplifierManager has ended with error: {:?}", e),
-                };
-            });
+            let amplifier_manager_task = tauri::async_runtime::spawn(supervise(
+                "AmplifierManager",
+                app.app_handle(),
+                amplifier_manager_channel_rx,
+                move |manager_rx| {
+                    let mut amplifier_manager = AmplifierManager::new(
+                        app_handle_manager_amp.clone(),
+                        event_bus_amp.clone(),
+                        manager_rx,
+                        amplifier_manager_channel_tx_clone.clone(),
+                    );
+
+                    async move { amplifier_manager.run().await }
+                },
+            ));
+
+            // Keep track of the manager tasks so shutdown can wait for them to end
+            app.manage(ManagerTasks(Mutex::new(vec![streammagic_manager_task, amplifier_manager_task])));
 
             // Hide the PunyTunes icon in the macOS dock. This prevents the "PunyTunes" menu from
             // appearing in the menu bar. The equivalent for Windows may not be necessary, but
@@ -133,7 +150,11 @@ fn main() {
                 }
                 SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
                     "quit" => {
-                        std::process::exit(0);
+                        let app_handle = app.app_handle();
+
+                        tauri::async_runtime::spawn(async move {
+                            shutdown::shutdown(&app_handle).await;
+                        });
                     }
                     _ => {}
                 },
@@ -1,12 +1,13 @@
 use std::fmt::Display;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use log;
 use serde;
 use serde::Serializer;
 use ts_rs::TS;
 
-#[derive(Debug, TS)]
+use crate::utils::epoch_millis_now;
+
+#[derive(Clone, Copy, Debug, TS)]
 #[ts(export, export_to = "../src/types/generated/AppMessageType.ts")]
 pub enum AppMessageType {
     AmplifierManagerState,
@@ -16,11 +17,18 @@ pub enum AppMessageType {
     IsActivating,
     IsDiscovering,
     IsInitializingStreamMagicManager,
+    StreamerActionResult,
+    StreamerCapabilities,
+    StreamerConnectionQuality,
+    StreamerSystemDisplay,
     StreamerSystemInfo,
     StreamerSystemPower,
     StreamerSystemSources,
+    StreamerSystemUpdate,
+    StreamerUnhandledPayloads,
     StreamerPresets,
     StreamerQueueList,
+    StreamerZoneAudio,
     StreamerZoneNowPlaying,
     StreamerZonePlayState,
     StreamerZonePlayStatePosition,
@@ -28,6 +36,7 @@ pub enum AppMessageType {
     StreamerZoneState,
     StreamMagicManagerState,
     StreamMagicManagerStatus,
+    SupervisorState,
     WebSocketClientStatus,
 }
 
@@ -84,15 +93,10 @@ pub struct AppLog {
 
 impl AppLog {
     pub fn new(level: log::Level, message: &str) -> Self {
-        let when = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(when) => when,
-            Err(_) => Duration::ZERO, // This really shouldn't happen
-        };
-
         AppLog {
             level: level.into(),
             message: message.to_string(),
-            when: when.as_millis(),
+            when: epoch_millis_now(),
         }
     }
 }
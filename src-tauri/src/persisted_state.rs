@@ -51,4 +51,12 @@ impl BackendState {
             Err(e) => Err(PunyTunesError::Store(format!("Could not delete backend store key: {:?}", e))),
         }
     }
+
+    /// Write the store to disk. Changes are already saved as they're made, so this is a final
+    /// safeguard for when the application is exiting.
+    pub fn flush(&mut self) -> Result<(), PunyTunesError> {
+        self.store
+            .save()
+            .map_err(|e| PunyTunesError::Store(format!("Could not save backend store: {:?}", e)))
+    }
 }
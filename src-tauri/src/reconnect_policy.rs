@@ -0,0 +1,207 @@
+//! A reconnection policy, shared by the StreamMagicManager and the AmplifierManager.
+//!
+//! When a connection can't be established (or is lost), a limited number of "fast" attempts are
+//! made. The first attempt is made immediately, with later attempts being delayed by an
+//! exponential backoff (with jitter) up to a maximum delay. Once the fast attempts have been
+//! exhausted, attempts continue in the "background" at a much longer interval until a connection
+//! is established (or the policy is reset).
+//!
+//! The policy only decides when each attempt should happen. Making the attempts is left to the
+//! managers: fast attempts are expected to be made after waiting for the returned delay, whereas
+//! background attempts are expected to be made once `take_due_background_attempt()` says so (as
+//! the managers can't wait that long without blocking everything else).
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ts_rs::TS;
+
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicyConfig {
+    // Delay before the second attempt. Each later attempt doubles the delay.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    // How much each delay is randomly shortened or lengthened by, as a fraction of the delay
+    pub jitter: f64,
+    // Number of attempts to make before switching to background attempts
+    pub max_fast_attempts: usize,
+    pub background_interval: Duration,
+}
+
+/// Reconnection details which might be of interest to the UI.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/ReconnectState.ts")]
+pub struct ReconnectState {
+    // Number of attempts made (or scheduled) since the policy was last reset
+    pub attempts: usize,
+    // Whether the fast attempts have been exhausted
+    pub is_background: bool,
+    // When the next attempt is due, in milliseconds since the epoch
+    pub next_attempt_at: Option<u128>,
+}
+
+pub struct ReconnectPolicy {
+    config: ReconnectPolicyConfig,
+    attempts: usize,
+    next_attempt_at: Option<Instant>,
+}
+
+impl ReconnectPolicy {
+    pub fn new(config: ReconnectPolicyConfig) -> Self {
+        ReconnectPolicy {
+            config,
+            attempts: 0,
+            next_attempt_at: None,
+        }
+    }
+
+    /// Forget about any previous attempts (e.g. once a connection has been established).
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+        self.next_attempt_at = None;
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Whether the fast attempts have been exhausted.
+    pub fn is_background(&self) -> bool {
+        self.attempts > self.config.max_fast_attempts
+    }
+
+    pub fn state(&self) -> ReconnectState {
+        // Attempts are scheduled on the monotonic clock, but the UI needs a wall clock time
+        let next_attempt_at = self.next_attempt_at.and_then(|when| {
+            (SystemTime::now() + when.saturating_duration_since(Instant::now()))
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since_epoch| since_epoch.as_millis())
+        });
+
+        ReconnectState {
+            attempts: self.attempts,
+            is_background: self.is_background(),
+            next_attempt_at,
+        }
+    }
+
+    /// Schedule the next attempt, returning how long to wait before making it.
+    pub fn schedule_next(&mut self, now: Instant) -> Duration {
+        self.attempts += 1;
+
+        let delay = self.delay(self.attempts, random_unit());
+        self.next_attempt_at = Some(now + delay);
+
+        delay
+    }
+
+    /// Whether a scheduled background attempt is now due. Returns true only once per scheduled
+    /// attempt.
+    pub fn take_due_background_attempt(&mut self, now: Instant) -> bool {
+        let is_due = self.is_background() && self.next_attempt_at.is_some_and(|when| when <= now);
+
+        if is_due {
+            self.next_attempt_at = None;
+        }
+
+        is_due
+    }
+
+    /// The delay before the given attempt (starting at 1). `random` is expected to be between 0.0
+    /// and 1.0, and determines the jitter applied to the delay.
+    fn delay(&self, attempt: usize, random: f64) -> Duration {
+        let delay = if attempt <= 1 {
+            Duration::ZERO
+        } else if attempt <= self.config.max_fast_attempts {
+            let multiplier = 2u32.saturating_pow((attempt - 2).try_into().unwrap_or(u32::MAX));
+            self.config.max_delay.min(self.config.base_delay.saturating_mul(multiplier))
+        } else {
+            self.config.background_interval
+        };
+
+        delay.mul_f64((1.0 + self.config.jitter * (random * 2.0 - 1.0)).max(0.0))
+    }
+}
+
+/// A random number between 0.0 and 1.0. This is only used for jitter, so std's randomly-seeded
+/// hasher is good enough (and avoids a dependency).
+fn random_unit() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    random as f64 / u64::MAX as f64
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(jitter: f64) -> ReconnectPolicyConfig {
+        ReconnectPolicyConfig {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            jitter,
+            max_fast_attempts: 4,
+            background_interval: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_backs_off_up_to_max_delay() {
+        let policy = ReconnectPolicy::new(config(0.0));
+
+        let delays: Vec<u128> = (1..=6).map(|attempt| policy.delay(attempt, 0.5).as_millis()).collect();
+
+        assert_eq!(delays, vec![0, 100, 200, 300, 60000, 60000]);
+    }
+
+    #[test]
+    fn test_applies_jitter() {
+        let policy = ReconnectPolicy::new(config(0.5));
+
+        assert_eq!(policy.delay(1, 1.0), Duration::ZERO);
+        assert_eq!(policy.delay(3, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.delay(3, 0.5), Duration::from_millis(200));
+        assert_eq!(policy.delay(3, 1.0), Duration::from_millis(300));
+
+        let mut policy = ReconnectPolicy::new(config(0.5));
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            let delay = policy.schedule_next(now);
+            assert!(delay <= Duration::from_secs(90));
+        }
+    }
+
+    #[test]
+    fn test_switches_to_background_attempts() {
+        let mut policy = ReconnectPolicy::new(config(0.0));
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            policy.schedule_next(now);
+            assert!(!policy.is_background());
+
+            // Fast attempts are never made in the background
+            assert!(!policy.take_due_background_attempt(now + Duration::from_secs(1)));
+        }
+
+        assert_eq!(policy.schedule_next(now), Duration::from_secs(60));
+        assert!(policy.is_background());
+        assert_eq!(policy.state().attempts, 5);
+        assert!(policy.state().is_background);
+
+        assert!(!policy.take_due_background_attempt(now + Duration::from_secs(59)));
+        assert!(policy.take_due_background_attempt(now + Duration::from_secs(60)));
+        assert!(!policy.take_due_background_attempt(now + Duration::from_secs(61)));
+        assert_eq!(policy.state().next_attempt_at, None);
+
+        policy.reset();
+        assert_eq!(policy.state(), ReconnectState::default());
+    }
+}
@@ -0,0 +1,89 @@
+//! Resume detection, shared by the StreamMagicManager and the AmplifierManager.
+//!
+//! Connections often don't survive the machine going to sleep, but a dead connection can take a
+//! while to notice (by way of missed pings or heartbeats). Instead, the managers pass each of their
+//! interval ticks to a `ResumeDetector`, which notices when a tick arrives much later than
+//! expected. The managers can then test their connections straight away.
+//!
+//! The monotonic clock doesn't advance during sleep on some platforms (e.g. macOS and Linux), so
+//! the gap between ticks is measured on both the monotonic and wall clocks, and the larger of the
+//! two is used. This means a wall clock jump (e.g. an NTP sync) can also look like a resume, but
+//! the only consequence of that is an unnecessary connection test.
+
+use std::time::{Duration, Instant, SystemTime};
+
+pub struct ResumeDetector {
+    // Gap between ticks which is considered to indicate a resume
+    threshold: Duration,
+    last_tick: Option<(Instant, SystemTime)>,
+}
+
+impl ResumeDetector {
+    pub fn new(threshold: Duration) -> Self {
+        ResumeDetector {
+            threshold,
+            last_tick: None,
+        }
+    }
+
+    /// Record an interval tick. If the gap since the previous tick exceeds the threshold, then the
+    /// gap is returned.
+    pub fn tick(&mut self, now: Instant, wall_now: SystemTime) -> Option<Duration> {
+        let gap = self.last_tick.map(|(last, wall_last)| {
+            // The wall clock can go backwards, in which case only the monotonic gap is used
+            let wall_gap = wall_now.duration_since(wall_last).unwrap_or(Duration::ZERO);
+
+            now.duration_since(last).max(wall_gap)
+        });
+
+        self.last_tick = Some((now, wall_now));
+
+        gap.filter(|gap| *gap > self.threshold)
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignores_regular_ticks() {
+        let mut detector = ResumeDetector::new(Duration::from_secs(5));
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+
+        for secs in 0..10 {
+            let elapsed = Duration::from_secs(secs);
+            assert_eq!(detector.tick(now + elapsed, wall_now + elapsed), None);
+        }
+    }
+
+    #[test]
+    fn test_detects_gaps_on_either_clock() {
+        let mut detector = ResumeDetector::new(Duration::from_secs(5));
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+
+        detector.tick(now, wall_now);
+
+        // The monotonic clock stopped during sleep, but the wall clock didn't
+        let wall_later = wall_now + Duration::from_secs(3600);
+        assert_eq!(
+            detector.tick(now + Duration::from_secs(1), wall_later),
+            Some(Duration::from_secs(3600))
+        );
+
+        // The process was suspended without the wall clock noticing
+        assert_eq!(
+            detector.tick(now + Duration::from_secs(31), wall_later + Duration::from_secs(1)),
+            Some(Duration::from_secs(30))
+        );
+
+        // The wall clock went backwards
+        assert_eq!(detector.tick(now + Duration::from_secs(32), wall_now), None);
+    }
+}
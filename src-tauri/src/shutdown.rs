@@ -0,0 +1,74 @@
+//! Coordinated application shutdown, used by both the `shutdown` command and the tray "Quit" item.
+//!
+//! Both managers are asked to shut down, which stops the WebSocketClient and the AmplifierHandler.
+//! The manager tasks are then given a limited time to end (so a stuck connection can't prevent
+//! the application from exiting), after which the persisted backend state is flushed to disk and
+//! the application exits.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use futures_util::future::join_all;
+use log::Level::{Error, Info, Warn};
+use tauri::{AppHandle, Manager};
+use tokio::time::{timeout, Duration};
+
+use crate::amplifier_manager::{AmplifierManagerAction, AmplifierManagerChannel};
+use crate::macros::{send_amplifier_manager_action_impl, send_manager_action_impl};
+use crate::state::PersistedBackendState;
+use crate::streammagic_manager::{StreamMagicManagerAction, StreamMagicManagerChannel};
+
+// How long to wait for the managers to end before exiting anyway
+const MANAGER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+static IS_SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// The manager tasks (the supervisors of the StreamMagicManager and AmplifierManager), so
+/// shutdown can wait for them to end.
+pub struct ManagerTasks(pub Mutex<Vec<tauri::async_runtime::JoinHandle<()>>>);
+
+/// Whether the application is shutting down (so managers ending is expected).
+pub fn is_shutting_down() -> bool {
+    IS_SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Shut down the application, exiting once shutdown is complete. Only the first call shuts
+/// down; calls made while a shutdown is already in progress return straight away.
+pub async fn shutdown(app_handle: &AppHandle) {
+    if IS_SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        send_app_log!(Info, "PunyTunes shutdown is already in progress");
+        return;
+    }
+
+    send_app_log!(Info, "PunyTunes is shutting down");
+
+    let streammagic_manager_channel = app_handle.state::<StreamMagicManagerChannel>().0.clone();
+    let amplifier_manager_channel = app_handle.state::<AmplifierManagerChannel>().0.clone();
+
+    send_manager_action_impl(&streammagic_manager_channel, StreamMagicManagerAction::ShutDown).await;
+    send_amplifier_manager_action_impl(&amplifier_manager_channel, AmplifierManagerAction::ShutDown).await;
+
+    let manager_tasks = match app_handle.try_state::<ManagerTasks>() {
+        Some(tasks) => std::mem::take(&mut *tasks.0.lock().unwrap()),
+        None => Vec::new(),
+    };
+
+    match timeout(MANAGER_SHUTDOWN_TIMEOUT, join_all(manager_tasks)).await {
+        Ok(_) => send_app_log!(Info, "Managers have ended"),
+        Err(_) => send_app_log!(
+            Warn,
+            "Managers did not end within {}s; exiting anyway",
+            MANAGER_SHUTDOWN_TIMEOUT.as_secs()
+        ),
+    }
+
+    let persisted_backend_state = app_handle.state::<PersistedBackendState>();
+
+    if let Err(e) = persisted_backend_state.0.lock().unwrap().flush() {
+        send_app_log!(Error, "Could not flush persisted state: {:?}", e);
+    }
+
+    send_app_log!(Info, "PunyTunes shutdown complete");
+
+    app_handle.exit(0);
+}
@@ -1,45 +1,94 @@
-use std::time::SystemTime;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime};
 
-use log::{info, Level::{Debug, Error, Info, Warn}};
+use log::Level::{Debug, Error, Info, Warn};
 use serde;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_store::JsonValue;
 use tokio::select;
 use tokio::sync::mpsc;
-use tokio::time::{Duration, sleep};
+use tokio::time::{Duration, sleep_until};
 use ts_rs::TS;
 
+use capabilities::StreamerCapabilities;
+use connection_quality::ConnectionQuality;
+use connection_state::{ConnectionEffect, ConnectionEvent, ConnectionStateMachine};
 use discovery::{discover_streamers, StreamMagicDevice};
+use outgoing_queue::{OutgoingQueue, QueuedAction};
+use pending_requests::{PendingRequest, PendingRequests};
+use queue_cache::QueueCache;
+use unhandled_payloads::UnhandledPayloads;
 use payloads::{
-    RequestUpdates, StreamerSystemPower, StreamerZonePosition, StreamMagicMessage, SystemInfo, SystemPower,
-    ZonePosition,
+    ControlBusMode, DisplayBrightness, RequestUpdates, StreamerSystemDisplay, StreamerZoneAudio, StreamerSystemUpdate,
+    StreamerZonePosition, StreamMagicMessage, StreamMagicRequest, StreamRadio, SystemDisplay, SystemInfo, SystemPower,
+    SystemUpdate, SystemUpdateAction, ZoneAudio, ZonePosition,
 };
 use websocket_client::{
     WebSocketClient, WebSocketClientAction, WebSocketClientStatus, WSClientRxChannelMsg, WSClientTxChannelMsg,
 };
 
 use crate::errors::PunyTunesError;
-use crate::messaging::{AppLog, AppMessageType};
-use crate::persisted_state::KEY_LAST_CONNECTED_HOST;
+use crate::event_bus::{AppEvent, EventBus};
+use crate::messaging::AppMessageType;
+use crate::persisted_state::{KEY_LAST_ACTIVATED_UDN, KEY_LAST_CONNECTED_HOST};
+use crate::reconnect_policy::{ReconnectPolicyConfig, ReconnectState};
+use crate::resume_detector::ResumeDetector;
 use crate::state::PersistedBackendState;
 use crate::streammagic_manager::payloads::{
-    PlayControl, Presets, QueueInfo, QueueList, RecallPreset, StreamerPresets, StreamerQueueInfo, StreamerQueueList,
-    StreamerSystemInfo, StreamerSystemSources, StreamerZoneNowPlaying, StreamerZonePlayState, StreamerZoneState,
-    SystemSources, TransportToggleState, ZoneNowPlaying, ZonePlayState, ZoneState,
+    PlayControl, PresetControl, Presets, QueueAddPosition, QueueControl, QueueInfo, QueueListParams, RecallPreset,
+    Source, StreamerPresets, StreamerQueueInfo, StreamerQueueList, StreamerSystemInfo, StreamerSystemSources,
+    StreamerZoneNowPlaying, SystemSources, TransportToggleState, ZoneNowPlaying, ZonePayload, ZonePlayState,
+    ZoneState,
 };
 use crate::traits::CustomEmitters;
 use crate::utils::host_from_url;
 
+mod capabilities;
+mod connection_quality;
+mod connection_state;
 mod discovery;
+mod outgoing_queue;
 mod payloads;
+mod pending_requests;
+mod queue_cache;
+mod unhandled_payloads;
 mod websocket_client;
 
+// Published to the event bus
+pub use connection_state::ConnectionState;
+pub use payloads::{StreamerSystemPower, StreamerZonePlayState, StreamerZoneState};
+
+// Number of queue items requested per /queue/list request
+const QUEUE_PAGE_SIZE: i32 = 100;
+
+// How long to wait for a requested /queue/list page before requesting it again
+const QUEUE_PAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Number of StreamerActions which can be queued while there's no Streamer connection
+const OUTGOING_QUEUE_SIZE: usize = 20;
+
+// Number of StreamMagic paths with unhandled payloads retained, and payloads retained per path
+const UNHANDLED_PAYLOAD_PATHS: usize = 20;
+const UNHANDLED_PAYLOADS_PER_PATH: usize = 10;
+
+// Gap between interval ticks which is assumed to mean the machine has resumed from sleep
+const RESUME_THRESHOLD: Duration = Duration::from_secs(5);
+
+// Streamer activation and reconnection timing. Fast attempts cover roughly 15 seconds, after
+// which an attempt is made every minute.
+const RECONNECT_POLICY: ReconnectPolicyConfig = ReconnectPolicyConfig {
+    base_delay: Duration::from_millis(200),
+    max_delay: Duration::from_millis(1500),
+    jitter: 0.2,
+    max_fast_attempts: 12,
+    background_interval: Duration::from_secs(60),
+};
+
 // ================================================================================================
-// TODO: StreamMagicManager and WebSocketClient both handle a bunch of "what's happening right
-//  now" state, which is scattered throughout their respective implementations. As a result, it's
-//  not easy to understand exactly what can and should be done when certain messages are received,
-//  or when other actions happen. Consider a state machine approach to make this easier to reason
-//  about, and (hopefully) more robust/predictable. Consider: https://crates.io/crates/rust-fsm
+// NOTE: The StreamMagicManager's connection-related state (discovery, activation, connection
+//  tests, and reconnection) is owned by a ConnectionStateMachine (see connection_state.rs). Any
+//  connection-related happening should be passed to handle_connection_event(), rather than
+//  tracking "what's happening right now" state elsewhere in the manager.
 // ------------------------------------------------------------------------------------------------
 
 /// Channel actions which can be sent from the invoker (the main app) to the StreamMagicManager
@@ -56,7 +105,6 @@ pub enum StreamMagicManagerAction {
     DisconnectFromStreamer,
     // bool is whether to auto-activate the first discovered device
     Discover(bool),
-    EmitAppLog(AppLog),
     HandleClientError,
     OnUIReady,
     ProcessDiscoveredDevice(StreamMagicDevice),
@@ -68,41 +116,128 @@ pub enum StreamMagicManagerAction {
 }
 
 /// Actions which can be performed on the Streamer.
-#[derive(Clone, Debug, serde::Deserialize, TS)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/StreamerAction.ts")]
 pub enum StreamerAction {
+    // Perform an action in a specific zone (rather than the Streamer's default zone)
+    InZone {
+        zone: String,
+        action: Box<StreamerAction>,
+    },
     MuteSet(bool),
     NextTrack,
     Pause,
     Play,
+    // Play the Airable radio station with the given id. Station ids fit within a JavaScript number,
+    // and the UI can't send a bigint (it's serialized as JSON).
+    PlayAirableRadio(#[ts(type = "number")] i64),
     PlayPresetId(i32),
     PlayQueueId(i32),
+    // Play an internet radio stream
+    PlayRadioUrl {
+        url: String,
+        name: String,
+        art_url: Option<String>,
+    },
     PowerOn,
     PowerStandby,
     PowerToggle,
+    // Clear the preset slot with the given id
+    PresetClear(i32),
+    PresetRename {
+        id: i32,
+        name: String,
+    },
+    // Store the currently-playing stream in the preset slot with the given id
+    PresetStore(i32),
     PreviousTrack,
+    // Add a media item (by URL) to the queue
+    QueueAdd {
+        url: String,
+        title: Option<String>,
+        position: QueueAddPosition,
+    },
+    QueueClear,
+    // Move the queue item with the given id to the given queue position
+    QueueMove {
+        id: i32,
+        position: i32,
+    },
+    QueueRemoveIds(Vec<i32>),
     Seek(i32),
+    // Seek to a percentage (0-100) of the current track's duration
+    SeekPercent(u8),
+    // Seek by the given number of seconds (positive or negative) from the current position
+    SeekRelative(i32),
+    SetBalance(i8),
+    SetControlBusMode(ControlBusMode),
+    SetDisplayBrightness(DisplayBrightness),
+    SetPreAmpMode(bool),
     SetRepeat(TransportToggleState),
     SetShuffle(TransportToggleState),
     SetSourceId(String),
+    SetToneBass(i8),
+    SetToneTreble(i8),
+    // Set the gain (dB) of the user EQ band with the given index
+    SetUserEqBandGain {
+        index: u8,
+        gain: f32,
+    },
+    SetUserEqEnabled(bool),
+    // Rename the source with the given id. Only sources which are nameable can be renamed.
+    SourceRename {
+        id: String,
+        name: String,
+    },
+    // Show or hide the source with the given id
+    SourceSetVisible {
+        id: String,
+        is_visible: bool,
+    },
+    // Reorder the sources. Vec is source ids in their new order.
+    SourcesReorder(Vec<String>),
     Stop,
+    // Ask the Streamer to check for (or start) a firmware update
+    SystemUpdate(SystemUpdateAction),
     TogglePlayback,
     VolumePercentSet(u8),
     VolumeStepChange(i8),
     VolumeStepSet(u8),
 }
 
+impl StreamerAction {
+    /// The zone an action targets (if any), and the action to perform in that zone. The outermost
+    /// zone wins when InZone actions are nested.
+    pub fn zone_and_action(&self) -> (Option<&str>, &StreamerAction) {
+        match self {
+            StreamerAction::InZone { zone, action } => (Some(zone.as_str()), action.zone_and_action().1),
+            action => (None, action),
+        }
+    }
+}
+
 /// All StreamMagicManager state, excluding Streamer payloads, which might be of interest to the UI.
 #[derive(Clone, Debug, serde::Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/StreamMagicManagerStateMsg.ts")]
 pub struct StreamMagicManagerStateMsg {
     devices: Vec<StreamMagicDevice>,
-    is_activating: bool,
+    connection_state: ConnectionState,
     is_discovering: bool,
-    is_testing_connection: bool,
+    reconnect: ReconnectState,
     websocket_client_status: WebSocketClientStatus,
 }
 
+/// The outcome of a `StreamerAction` which the Streamer rejected, or never replied to.
+#[derive(Clone, Debug, serde::Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/StreamerActionResult.ts")]
+pub struct StreamerActionResult {
+    action: StreamerAction,
+    // The Streamer's result code. None if no reply was received.
+    result: Option<i32>,
+    message: String,
+    timed_out: bool,
+}
+
 // ------------------------------------------------------------------------------------------------
 
 pub struct StreamMagicManagerChannel(pub mpsc::Sender<StreamMagicManagerChannelMsg>);
@@ -126,93 +261,126 @@ pub enum StreamMagicManagerChannelMsg {
 ///       * `StreamMagicManagerAction`: General actions (discovery, Streamer activation, etc).
 ///   * Manages WebSocketClient connections to the Streamer.
 ///       * Registers for Streamer updates.
-///       * Converts Streamer actions to plain text messages for sending to the Streamer.
+///       * Converts Streamer actions to typed `StreamMagicRequest`s, which are serialized to plain
+///         text messages for sending to the Streamer.
 ///       * Receives plain text messages from the Streamer, parses them, stores them, and emits
 ///         them to the UI.
+///   * Publishes Streamer state changes (and connection state changes) to the event bus.
 pub struct StreamMagicManager {
     app_handle: AppHandle,
+    event_bus: EventBus,
     // Receive commands from the application or StreamMagicManager itself
     rx_channel: mpsc::Receiver<StreamMagicManagerChannelMsg>,
     // Send commands to itself (for use by spawned tasks such as discovery, which won't
     // have access to self)
     tx_channel: mpsc::Sender<StreamMagicManagerChannelMsg>,
 
-    // Buffer any AppLog sent to the UI before the UI is ready, so they can be sent all at once
-    // when the UI is ready for them. This buffer should then be unnecessary for the remainder of
-    // the session.
-    buffered_app_logs: Vec<AppLog>,
     // Discovered UPnP MediaRenderer devices from Cambridge Audio
     devices: Vec<StreamMagicDevice>,
     // Keep track of the device last successfully activated. Can help with reconnects. This is
     // tracked separately from the active device in "devices" as we want it to persist separate
     // from whatever device currently happens to be active (which is none after a disconnect).
     last_active_device: Option<StreamMagicDevice>,
-    // The StreamMagicManager can be activating a StreamMagicDevice (once discovery has completed),
-    // *or* activating the previously-persisted streamer by IP address (i.e. not yet a known
-    // StreamMagicDevice). Individual StreamMagicDevices also have an is_activating flag.
-    is_activating: bool,
-    is_discovering: bool,
-    is_testing_connection: bool,
-    count_of_disconnects_while_testing: usize,
-    activation_start: Option<SystemTime>,
-    activation_attempts: usize,
-    activation_timeout: u128,
-    ui_ready: bool,
+    // Discovery, activation, connection test, and reconnection state
+    connection: ConnectionStateMachine,
+    // The UDN of the device being activated. The StreamMagicManager can also be activating a
+    // streamer by IP address alone (see activating_host), in which case this is None. Individual
+    // StreamMagicDevices also have an is_activating flag.
+    activating_udn: Option<String>,
+    // The host being activated when there's no UDN to activate (e.g. a persisted host from a
+    // session which didn't persist the UDN)
+    activating_host: Option<String>,
+    // When a delayed connection attempt is due, and the host it's for. The attempt is made from
+    // the run loop, so the delay doesn't hold up anything else.
+    scheduled_connection: Option<(Instant, String)>,
+    // The last-connected host and (when known) its UDN, as persisted across sessions. The UDN
+    // allows the streamer to be found again if its host changes (e.g. after a DHCP lease change).
+    persisted_host: Option<String>,
+    persisted_udn: Option<String>,
     ws_client_join_handle: Option<tauri::async_runtime::JoinHandle<Result<(), PunyTunesError>>>,
     ws_client_receive_channel: Option<mpsc::Receiver<WSClientTxChannelMsg>>,
     ws_client_send_channel: Option<mpsc::Sender<WSClientRxChannelMsg>>,
     ws_client_status: WebSocketClientStatus,
+    // Latency and lost pings for the current WebSocket connection
+    connection_quality: ConnectionQuality,
+    // Requests sent to the Streamer which have not yet received a response
+    pending_requests: PendingRequests,
+    // StreamerActions waiting for the Streamer connection to be re-established
+    outgoing_queue: OutgoingQueue,
+    // Queue items retrieved so far, used to assemble the full QueueList from paged responses
+    queue_cache: QueueCache,
+    // What the Streamer can currently do (in its default zone)
+    capabilities: StreamerCapabilities,
+    // Payload data we don't know how to handle, retained for inspection in the UI
+    unhandled_payloads: UnhandledPayloads,
 
     // Streamer payloads. Only the last (most recent) payload received is retained.
     presets: Option<StreamerPresets>,
     queue_info: Option<StreamerQueueInfo>,
     queue_list: Option<StreamerQueueList>,
     system_info: Option<StreamerSystemInfo>,
+    system_display: Option<StreamerSystemDisplay>,
     system_power: Option<StreamerSystemPower>,
     system_sources: Option<StreamerSystemSources>,
-    zone_now_playing: Option<StreamerZoneNowPlaying>,
-    zone_play_state: Option<StreamerZonePlayState>,
-    zone_position: Option<StreamerZonePosition>,
-    zone_state: Option<StreamerZoneState>,
+    system_update: Option<StreamerSystemUpdate>,
+
+    // Zone payloads, keyed by zone id. Zones are listed in the order the Streamer first reported
+    // them; the first zone is treated as the default zone.
+    zones: Vec<String>,
+    zone_audio: HashMap<String, StreamerZoneAudio>,
+    zone_now_playing: HashMap<String, StreamerZoneNowPlaying>,
+    zone_play_state: HashMap<String, StreamerZonePlayState>,
+    zone_position: HashMap<String, StreamerZonePosition>,
+    zone_state: HashMap<String, StreamerZoneState>,
 }
 
 impl StreamMagicManager {
     pub fn new(
         app_handle: AppHandle,
+        event_bus: EventBus,
         rx_channel: mpsc::Receiver<StreamMagicManagerChannelMsg>,
         tx_channel: mpsc::Sender<StreamMagicManagerChannelMsg>,
     ) -> StreamMagicManager {
         StreamMagicManager {
             app_handle,
+            event_bus,
             rx_channel,
             tx_channel,
 
-            buffered_app_logs: Vec::new(),
             devices: Vec::new(),
             last_active_device: None,
-            is_activating: false,
-            is_discovering: false,
-            is_testing_connection: false,
-            count_of_disconnects_while_testing: 0,
-            activation_start: None,
-            activation_attempts: 0,
-            activation_timeout: 15000,
-            ui_ready: false,
+            connection: ConnectionStateMachine::new(RECONNECT_POLICY),
+            activating_udn: None,
+            activating_host: None,
+            scheduled_connection: None,
+            persisted_host: None,
+            persisted_udn: None,
             ws_client_join_handle: None,
             ws_client_receive_channel: None,
             ws_client_send_channel: None,
             ws_client_status: WebSocketClientStatus::Disconnected(Default::default()),
+            connection_quality: ConnectionQuality::default(),
+            pending_requests: PendingRequests::new(Duration::from_millis(5000)),
+            outgoing_queue: OutgoingQueue::new(OUTGOING_QUEUE_SIZE),
+            queue_cache: QueueCache::new(QUEUE_PAGE_SIZE, QUEUE_PAGE_TIMEOUT),
+            capabilities: StreamerCapabilities::default(),
+            unhandled_payloads: UnhandledPayloads::new(UNHANDLED_PAYLOAD_PATHS, UNHANDLED_PAYLOADS_PER_PATH),
 
             presets: None,
             queue_info: None,
             queue_list: None,
             system_info: None,
+            system_display: None,
             system_power: None,
             system_sources: None,
-            zone_now_playing: None,
-            zone_play_state: None,
-            zone_position: None,
-            zone_state: None,
+            system_update: None,
+
+            zones: Vec::new(),
+            zone_audio: HashMap::new(),
+            zone_now_playing: HashMap::new(),
+            zone_play_state: HashMap::new(),
+            zone_position: HashMap::new(),
+            zone_state: HashMap::new(),
         }
     }
 
@@ -225,9 +393,9 @@ impl StreamMagicManager {
                 AppMessageType::StreamMagicManagerState,
                 StreamMagicManagerStateMsg {
                     devices: self.devices.clone(),
-                    is_activating: self.is_activating,
-                    is_discovering: self.is_discovering,
-                    is_testing_connection: self.is_testing_connection,
+                    connection_state: self.connection.state().clone(),
+                    is_discovering: self.connection.is_discovering(),
+                    reconnect: self.connection.reconnect_state(),
                     websocket_client_status: self.ws_client_status.clone(),
                 },
             )
@@ -236,34 +404,69 @@ impl StreamMagicManager {
 
     async fn emit_streammagic_payload(&self, msg_type: AppMessageType) {
         match msg_type {
+            AppMessageType::StreamerCapabilities => {
+                self.app_handle.emit_app_message(msg_type, &self.capabilities).await
+            }
+            AppMessageType::StreamerConnectionQuality => {
+                self.app_handle.emit_app_message(msg_type, &self.connection_quality).await
+            }
             AppMessageType::StreamerQueueList => self.app_handle.emit_app_message(msg_type, &self.queue_list).await,
             AppMessageType::StreamerPresets => self.app_handle.emit_app_message(msg_type, &self.presets).await,
+            AppMessageType::StreamerSystemDisplay => {
+                self.app_handle.emit_app_message(msg_type, &self.system_display).await
+            }
             AppMessageType::StreamerSystemInfo => self.app_handle.emit_app_message(msg_type, &self.system_info).await,
             AppMessageType::StreamerSystemPower => self.app_handle.emit_app_message(msg_type, &self.system_power).await,
             AppMessageType::StreamerSystemSources => {
                 self.app_handle.emit_app_message(msg_type, &self.system_sources).await
             }
-            AppMessageType::StreamerZoneNowPlaying => {
-                self.app_handle.emit_app_message(msg_type, &self.zone_now_playing).await
-            }
-            AppMessageType::StreamerZonePlayState => {
-                self.app_handle.emit_app_message(msg_type, &self.zone_play_state).await
+            AppMessageType::StreamerSystemUpdate => {
+                self.app_handle.emit_app_message(msg_type, &self.system_update).await
             }
-            AppMessageType::StreamerZonePosition => {
-                self.app_handle.emit_app_message(msg_type, &self.zone_position).await
+            AppMessageType::StreamerUnhandledPayloads => {
+                self.app_handle.emit_app_message(msg_type, self.unhandled_payloads.all()).await
             }
-            AppMessageType::StreamerZoneState => self.app_handle.emit_app_message(msg_type, &self.zone_state).await,
+            AppMessageType::StreamerZoneAudio => self.emit_zone_payloads(msg_type, &self.zone_audio).await,
+            AppMessageType::StreamerZoneNowPlaying => self.emit_zone_payloads(msg_type, &self.zone_now_playing).await,
+            AppMessageType::StreamerZonePlayState => self.emit_zone_payloads(msg_type, &self.zone_play_state).await,
+            AppMessageType::StreamerZonePosition => self.emit_zone_payloads(msg_type, &self.zone_position).await,
+            AppMessageType::StreamerZoneState => self.emit_zone_payloads(msg_type, &self.zone_state).await,
             _ => {}
         }
     }
 
+    /// Emit the payload for every zone. Zone payloads include the zone they describe, so the UI
+    /// can tell them apart.
+    async fn emit_zone_payloads<T: serde::Serialize + Sync>(
+        &self,
+        msg_type: AppMessageType,
+        payloads: &HashMap<String, T>,
+    ) {
+        for zone in &self.zones {
+            self.emit_zone_payload(msg_type, payloads.get(zone)).await;
+        }
+    }
+
+    async fn emit_zone_payload<T: serde::Serialize + Sync>(&self, msg_type: AppMessageType, payload: Option<&T>) {
+        if let Some(payload) = payload {
+            self.app_handle.emit_app_message(msg_type, payload).await;
+        }
+    }
+
     async fn emit_streammagic_payloads(&self) {
+        self.emit_streammagic_payload(AppMessageType::StreamerCapabilities).await;
+        self.emit_streammagic_payload(AppMessageType::StreamerConnectionQuality).await;
         self.emit_streammagic_payload(AppMessageType::StreamerQueueList).await;
         self.emit_streammagic_payload(AppMessageType::StreamerPresets).await;
+        self.emit_streammagic_payload(AppMessageType::StreamerSystemDisplay).await;
         self.emit_streammagic_payload(AppMessageType::StreamerSystemInfo).await;
         self.emit_streammagic_payload(AppMessageType::StreamerSystemPower).await;
         self.emit_streammagic_payload(AppMessageType::StreamerSystemSources)
             .await;
+        self.emit_streammagic_payload(AppMessageType::StreamerSystemUpdate).await;
+        self.emit_streammagic_payload(AppMessageType::StreamerUnhandledPayloads)
+            .await;
+        self.emit_streammagic_payload(AppMessageType::StreamerZoneAudio).await;
         self.emit_streammagic_payload(AppMessageType::StreamerZoneNowPlaying)
             .await;
         self.emit_streammagic_payload(AppMessageType::StreamerZonePlayState)
@@ -273,6 +476,41 @@ impl StreamMagicManager {
         self.emit_streammagic_payload(AppMessageType::StreamerZoneState).await;
     }
 
+    async fn emit_streamer_action_result(&self, result: StreamerActionResult) {
+        self.app_handle
+            .emit_app_message(AppMessageType::StreamerActionResult, result)
+            .await;
+    }
+
+    /// Report a StreamerAction which was not sent to the Streamer because it cannot succeed.
+    async fn reject_streamer_action(&self, action: StreamerAction, reason: &str) {
+        send_app_log!(Warn, "Not sending {:?} to streamer: {}", action, reason);
+
+        self.emit_streamer_action_result(StreamerActionResult {
+            action,
+            result: None,
+            message: reason.to_string(),
+            timed_out: false,
+        })
+        .await;
+    }
+
+    /// Report actions which were queued while there was no Streamer connection, but which were
+    /// never sent.
+    async fn report_unsent_actions(&self, queued_actions: Vec<QueuedAction>, reason: &str) {
+        for queued in queued_actions {
+            send_app_log!(Warn, "{:?} was not sent to streamer: {}", &queued.action, reason);
+
+            self.emit_streamer_action_result(StreamerActionResult {
+                action: queued.action,
+                result: None,
+                message: reason.to_string(),
+                timed_out: true,
+            })
+            .await;
+        }
+    }
+
     // --------------------------------------------------------------------------------------------
     // State handling
 
@@ -288,6 +526,68 @@ impl StreamMagicManager {
         }
     }
 
+    /// Persist the connected streamer's host (and UDN, when known) for use by later sessions.
+    fn persist_connected_device(&mut self, host: &str, udn: Option<String>) {
+        // A device which hasn't been discovered yet (e.g. when connecting to the persisted host
+        // before discovery has completed) is assumed to be the persisted device if its host is
+        // unchanged.
+        let udn = udn.or_else(|| match self.persisted_host.as_deref() == Some(host) {
+            true => self.persisted_udn.clone(),
+            false => None,
+        });
+
+        if self.persisted_host.as_deref() == Some(host) && self.persisted_udn == udn {
+            return;
+        }
+
+        send_app_log!(
+            Info,
+            "Persisting last connected host: {} (UDN: {})",
+            host, udn.as_deref().unwrap_or("unknown")
+        );
+
+        {
+            let persisted_backend_state = self.app_handle.state::<PersistedBackendState>();
+            let mut state_guard = persisted_backend_state.0.lock().unwrap();
+
+            if let Err(set_error) = state_guard.set(KEY_LAST_CONNECTED_HOST, host.into()) {
+                send_app_log!(Error, "Could not persist last connected host: {:?}", set_error);
+            }
+
+            let udn_result = match &udn {
+                Some(udn) => state_guard.set(KEY_LAST_ACTIVATED_UDN, udn.as_str().into()),
+                None => state_guard.delete(KEY_LAST_ACTIVATED_UDN).map(|_| ()),
+            };
+
+            if let Err(udn_error) = udn_result {
+                send_app_log!(Error, "Could not persist last activated UDN: {:?}", udn_error);
+            }
+        }
+
+        self.persisted_host = Some(host.to_string());
+        self.persisted_udn = udn;
+    }
+
+    fn forget_persisted_device(&mut self) {
+        {
+            let persisted_backend_state = self.app_handle.state::<PersistedBackendState>();
+            let mut state_guard = persisted_backend_state.0.lock().unwrap();
+
+            for key in [KEY_LAST_CONNECTED_HOST, KEY_LAST_ACTIVATED_UDN] {
+                if let Err(delete_error) = state_guard.delete(key) {
+                    send_app_log!(
+                        Warn,
+                        "Could not remove {} from persisted state: {:?}",
+                        key, delete_error
+                    );
+                }
+            }
+        }
+
+        self.persisted_host = None;
+        self.persisted_udn = None;
+    }
+
     async fn on_state_changed(&self) {
         self.emit_manager_state().await;
     }
@@ -298,6 +598,13 @@ impl StreamMagicManager {
     }
 
     async fn add_device(&mut self, device: StreamMagicDevice) {
+        // If we're still trying to connect to this device, then it may have changed address since
+        // it was last connected to (e.g. after a DHCP lease change).
+        let udn = device.udn.clone();
+        let is_relocated_connection_target = self.connection.is_connecting()
+            && self.connection_target_udn().as_ref() == Some(&udn)
+            && host_from_url(&device.url) != self.last_known_host(&udn);
+
         self.devices.push(device);
 
         match self.ws_client_status.clone() {
@@ -308,48 +615,98 @@ impl StreamMagicManager {
         }
 
         self.on_state_changed().await;
+
+        if is_relocated_connection_target {
+            send_app_log!(Info, "Device {} found at a new address; reconnecting", &udn);
+            self.activate_device(&udn).await;
+        }
     }
 
     async fn reset_websocket_related_state(&mut self) {
         self.set_websocket_client_status(&WebSocketClientStatus::Disconnected(Default::default())).await;
-        self.set_is_testing_connection(false).await;
-        self.set_is_activating(false).await;
+        self.handle_connection_event(ConnectionEvent::Stopped).await;
     }
 
-    async fn set_is_activating(&mut self, is_activating: bool) {
-        if is_activating {
-            if !self.is_activating {
-                self.activation_start = Some(SystemTime::now());
-                self.activation_attempts = 0;
-                self.is_activating = true;
-            }
-        } else {
-            self.activation_start = None;
-            self.activation_attempts = 0;
-            self.is_activating = false;
-        }
+    async fn set_is_discovering(&mut self, is_discovering: bool) {
+        let event = match is_discovering {
+            true => ConnectionEvent::DiscoveryStarted,
+            false => ConnectionEvent::DiscoveryEnded,
+        };
 
-        self.on_state_changed().await;
+        self.handle_connection_event(event).await;
     }
 
-    async fn set_is_discovering(&mut self, is_discovering: bool) {
-        self.is_discovering = is_discovering;
+    /// Pass a connection-related event to the connection state machine, and carry out whatever
+    /// the state machine decides needs to happen as a result.
+    async fn handle_connection_event(&mut self, event: ConnectionEvent) {
+        let effect = self.transition_connection_state(event);
         self.on_state_changed().await;
+
+        match effect {
+            ConnectionEffect::None => {}
+            ConnectionEffect::Connect(delay) => self.connect_to_target(delay).await,
+            ConnectionEffect::Test => {
+                // A test which can't be requested is treated like a failed connection. This is
+                // handled here, as the manager can't wait on sending an action to its own channel.
+                if !self.send_test_connection_request_to_websocket_client().await {
+                    let effect = self.transition_connection_state(ConnectionEvent::ClientFailed);
+                    self.on_state_changed().await;
+
+                    if let ConnectionEffect::Connect(delay) = effect {
+                        self.connect_to_target(delay).await;
+                    }
+                }
+            }
+        }
     }
 
-    async fn set_is_testing_connection(&mut self, is_testing_connection: bool) {
-        send_app_log!(
-            &self.tx_channel, Debug, "Connection test {}",
-            if is_testing_connection { "starting" } else { "completed" }
-        );
+    fn transition_connection_state(&mut self, event: ConnectionEvent) -> ConnectionEffect {
+        let previous_state = self.connection.state().clone();
+        let effect = self.connection.handle(event, Instant::now());
+        let state = self.connection.state().clone();
 
-        self.is_testing_connection = is_testing_connection;
-        self.on_state_changed().await;
+        if state != previous_state {
+            // Connection tests happen every time the UI is opened, so are less noteworthy
+            let level = match (&previous_state, &state) {
+                (ConnectionState::Testing, _) | (_, ConnectionState::Testing) => Debug,
+                _ => Info,
+            };
+
+            send_app_log!(level, "Connection state: {:?} -> {:?}", previous_state, state);
+
+            if let ConnectionState::GivingUp(reason) = &state {
+                send_app_log!(Error, "Giving up on streamer connection: {}", reason);
+            }
+
+            self.event_bus.publish(AppEvent::StreamerConnectionState(state));
+        }
+
+        if !self.connection.is_connecting() {
+            self.activating_udn = None;
+            self.activating_host = None;
+            self.scheduled_connection = None;
+
+            for device in &mut self.devices {
+                device.is_activating = false;
+            }
+        }
+
+        effect
     }
 
     async fn set_websocket_client_status(&mut self, status: &WebSocketClientStatus) {
         self.ws_client_status = status.clone();
         self.on_state_changed().await;
+
+        // Connection quality only applies to the current connection
+        if matches!(status, WebSocketClientStatus::Disconnected(_)) && self.connection_quality != Default::default() {
+            self.set_connection_quality(ConnectionQuality::default()).await;
+        }
+    }
+
+    async fn set_connection_quality(&mut self, quality: ConnectionQuality) {
+        self.connection_quality = quality;
+        self.emit_streammagic_payload(AppMessageType::StreamerConnectionQuality).await;
     }
 
     // --------------------------------------------------------------------------------------------
@@ -359,16 +716,53 @@ impl StreamMagicManager {
         self.emit_streammagic_payload(AppMessageType::StreamerPresets).await;
     }
 
+    /// Whether the stream currently playing in the given zone (or the default zone) can be stored
+    /// as a preset. If the Streamer hasn't told us either way then we assume it can, and let the
+    /// Streamer decide.
+    fn is_presettable(&self, zone: Option<&str>) -> bool {
+        zone.or(self.default_zone())
+            .and_then(|zone| self.zone_play_state.get(zone))
+            .and_then(|play_state| play_state.play_state().presettable)
+            .unwrap_or(true)
+    }
+
     async fn set_queue_info(&mut self, queue_info: StreamerQueueInfo) {
+        // A QueueInfo update is used to trigger a retrieval of any queue items we haven't seen
+        // yet. If there are none then the queue can be assembled from the cache straight away.
+        let pages = self.queue_cache.set_queue_info(queue_info.queue_info(), Instant::now());
         self.queue_info = Some(queue_info);
 
         // NOTE: queue_info is not emitted to the UI. It's only for internal use, to trigger
-        //  full QueueList retrievals on queue updates.
+        //  QueueList retrievals on queue updates.
+
+        if pages.is_empty() {
+            self.emit_assembled_queue_list().await;
+        } else {
+            self.request_queue_pages(pages).await;
+        }
+    }
+
+    async fn request_queue_pages(&mut self, pages: Vec<QueueListParams>) {
+        for page in pages {
+            self.send_websocket_message(&StreamMagicRequest::QueueList(page)).await;
+        }
     }
 
     async fn set_queue_list(&mut self, queue_list: StreamerQueueList) {
-        self.queue_list = Some(queue_list);
-        self.emit_streammagic_payload(AppMessageType::StreamerQueueList).await;
+        // The QueueList received from the streamer may only be one page of the queue, so it's
+        // merged into the cache and only emitted once the full queue has been retrieved.
+        if let Some(next_page) = self.queue_cache.add_queue_list(queue_list.into_queue_list(), Instant::now()) {
+            self.send_websocket_message(&StreamMagicRequest::QueueList(next_page)).await;
+        }
+
+        self.emit_assembled_queue_list().await;
+    }
+
+    async fn emit_assembled_queue_list(&mut self) {
+        if let Some(queue_list) = self.queue_cache.assembled() {
+            self.queue_list = Some(StreamerQueueList::new(queue_list));
+            self.emit_streammagic_payload(AppMessageType::StreamerQueueList).await;
+        }
     }
 
     async fn set_system_info(&mut self, info: StreamerSystemInfo) {
@@ -376,7 +770,13 @@ impl StreamMagicManager {
         self.emit_streammagic_payload(AppMessageType::StreamerSystemInfo).await;
     }
 
+    async fn set_system_display(&mut self, display: StreamerSystemDisplay) {
+        self.system_display = Some(display);
+        self.emit_streammagic_payload(AppMessageType::StreamerSystemDisplay).await;
+    }
+
     async fn set_system_power(&mut self, power: StreamerSystemPower) {
+        self.event_bus.publish(AppEvent::StreamerSystemPower(power.clone()));
         self.system_power = Some(power);
         self.emit_streammagic_payload(AppMessageType::StreamerSystemPower).await;
     }
@@ -387,27 +787,88 @@ impl StreamMagicManager {
             .await;
     }
 
+    async fn set_system_update(&mut self, update: StreamerSystemUpdate) {
+        self.system_update = Some(update);
+        self.emit_streammagic_payload(AppMessageType::StreamerSystemUpdate).await;
+    }
+
+    /// Determine what the Streamer can do in the given zone (or the default zone). Only the zone's
+    /// controls are considered (see the capabilities module for why SystemInfo isn't).
+    fn capabilities_for_zone(&self, zone: Option<&str>) -> StreamerCapabilities {
+        let now_playing = zone
+            .or(self.default_zone())
+            .and_then(|zone| self.zone_now_playing.get(zone))
+            .map(|now_playing| now_playing.now_playing());
+
+        StreamerCapabilities::new(now_playing.and_then(|now_playing| now_playing.controls.as_deref()))
+    }
+
+    /// Recompute the default zone's capabilities, emitting them if they've changed.
+    async fn update_capabilities(&mut self) {
+        let capabilities = self.capabilities_for_zone(None);
+
+        if capabilities != self.capabilities {
+            self.capabilities = capabilities;
+            self.emit_streammagic_payload(AppMessageType::StreamerCapabilities).await;
+        }
+    }
+
+    /// Record a zone reported by the Streamer, returning its id.
+    fn track_zone(&mut self, zone: &str) -> String {
+        if !self.zones.iter().any(|known_zone| known_zone == zone) {
+            self.zones.push(zone.to_string());
+        }
+
+        zone.to_string()
+    }
+
+    fn default_zone(&self) -> Option<&str> {
+        self.zones.first().map(String::as_str)
+    }
+
+    fn clear_zone_payloads(&mut self) {
+        self.zones.clear();
+        self.zone_audio.clear();
+        self.zone_now_playing.clear();
+        self.zone_play_state.clear();
+        self.zone_position.clear();
+        self.zone_state.clear();
+    }
+
+    async fn set_zone_audio(&mut self, audio: StreamerZoneAudio) {
+        let zone = self.track_zone(audio.zone());
+        self.zone_audio.insert(zone.clone(), audio);
+        self.emit_zone_payload(AppMessageType::StreamerZoneAudio, self.zone_audio.get(&zone)).await;
+    }
+
     async fn set_zone_now_playing(&mut self, now_playing: StreamerZoneNowPlaying) {
-        self.zone_now_playing = Some(now_playing);
-        self.emit_streammagic_payload(AppMessageType::StreamerZoneNowPlaying)
-            .await;
+        let zone = self.track_zone(now_playing.zone());
+        self.zone_now_playing.insert(zone.clone(), now_playing);
+        self.emit_zone_payload(AppMessageType::StreamerZoneNowPlaying, self.zone_now_playing.get(&zone)).await;
+        self.update_capabilities().await;
     }
 
     async fn set_zone_play_state(&mut self, play_state: StreamerZonePlayState) {
-        self.zone_play_state = Some(play_state);
-        self.emit_streammagic_payload(AppMessageType::StreamerZonePlayState)
-            .await;
+        let zone = self.track_zone(play_state.zone());
+        self.event_bus.publish(AppEvent::StreamerZonePlayState(Box::new(play_state.clone())));
+        self.zone_play_state.insert(zone.clone(), play_state);
+        self.emit_zone_payload(AppMessageType::StreamerZonePlayState, self.zone_play_state.get(&zone)).await;
     }
 
     async fn set_zone_position(&mut self, position: StreamerZonePosition) {
-        self.zone_position = Some(position);
-        self.emit_streammagic_payload(AppMessageType::StreamerZonePosition)
-            .await;
+        let zone = self.track_zone(position.zone());
+        self.zone_position.insert(zone.clone(), position);
+        self.emit_zone_payload(AppMessageType::StreamerZonePosition, self.zone_position.get(&zone)).await;
     }
 
     async fn set_zone_state(&mut self, state: StreamerZoneState) {
-        self.zone_state = Some(state);
-        self.emit_streammagic_payload(AppMessageType::StreamerZoneState).await;
+        // ZoneState payloads are complete, so they replace (rather than merge with) the previous
+        // state. Fields such as volume_percent are absent when not applicable (e.g. when pre-amp
+        // mode is disabled), and must not be retained from an earlier payload.
+        let zone = self.track_zone(state.zone());
+        self.event_bus.publish(AppEvent::StreamerZoneState(state.clone()));
+        self.zone_state.insert(zone.clone(), state);
+        self.emit_zone_payload(AppMessageType::StreamerZoneState, self.zone_state.get(&zone)).await;
     }
 
     // --------------------------------------------------------------------------------------------
@@ -421,7 +882,7 @@ impl StreamMagicManager {
     async fn do_discovery(&mut self, activate_discovered_device: bool) {
         // Will also optionally trigger a device activation.
 
-        send_app_log!(&self.tx_channel, Info, "Initiating discovery");
+        send_app_log!(Info, "Initiating discovery");
 
         self.set_is_discovering(true).await;
         self.clear_devices().await;
@@ -430,9 +891,9 @@ impl StreamMagicManager {
 
         tauri::async_runtime::spawn(async move {
             match discover_streamers(&channel_clone, activate_discovered_device).await {
-                Ok(()) => send_app_log!(&channel_clone, Info, "Streamer UPnP discovery complete"),
+                Ok(()) => send_app_log!(Info, "Streamer UPnP discovery complete"),
                 Err(e) => {
-                    send_app_log!(&channel_clone, Error, "Streamer discovery failed with error: {:?}", e);
+                    send_app_log!(Error, "Streamer discovery failed with error: {:?}", e);
                     send_manager_action!(&channel_clone, StreamMagicManagerAction::SetIsDiscovering(false));
                 }
             };
@@ -470,83 +931,124 @@ impl StreamMagicManager {
         }
 
         if let Some(device) = activated_device {
-            send_app_log!(&self.tx_channel, Info, "Marked device {} as active", device);
-            self.last_active_device = Some((*device).clone());
+            send_app_log!(Info, "Marked device {} as active", device);
+            let device = (*device).clone();
+
+            // Now that the active device is known, its UDN can be persisted alongside its host.
+            if let Some(host) = host_from_url(url) {
+                self.persist_connected_device(&host, Some(device.udn.clone()));
+            }
+
+            self.last_active_device = Some(device);
         }
 
         self.emit_manager_state().await;
     }
 
     async fn activate_device(&mut self, udn: &str) {
-        self.set_is_activating(true).await;
+        self.set_all_device_activation_false().await;
+        self.activating_udn = Some(udn.to_string());
+        self.activating_host = None;
 
-        let now = SystemTime::now();
+        self.handle_connection_event(ConnectionEvent::Activate).await;
+    }
 
-        // Check whether we've been attempting to activate for too long
-        if let Some(activation_start) = self.activation_start {
-            if let Ok(activating_duration) = now.duration_since(activation_start) {
-                if activating_duration.as_millis() > self.activation_timeout {
-                    send_app_log!(
-                        &self.tx_channel, Error,
-                        "Giving up attempting re-activation after {}ms (made {} attempts)",
-                        self.activation_timeout,
-                        self.activation_attempts
-                    );
+    /// Activate whatever device is at the given host, without knowing its UDN.
+    async fn activate_host(&mut self, host: &str) {
+        self.set_all_device_activation_false().await;
+        self.activating_udn = None;
+        self.activating_host = Some(host.to_string());
 
-                    self.set_is_activating(false).await;
+        self.handle_connection_event(ConnectionEvent::Activate).await;
+    }
+
+    /// Open a WebSocketClient connection to the device being activated, or (when reconnecting)
+    /// the last-active device. The device will be marked as active once the Connected
+    /// WebSocketClient status is received.
+    ///
+    /// A non-zero delay schedules the connection, which is then made from the run loop.
+    async fn connect_to_target(&mut self, delay: Duration) {
+        let (host, udn) = match self.activating_host.clone() {
+            Some(host) => (host, None),
+            None => {
+                let Some(udn) = self.connection_target_udn() else {
+                    send_app_log!(Warn, "No device to connect to (no last-active device)");
+                    self.transition_connection_state(ConnectionEvent::NoConnectionTarget);
+                    self.on_state_changed().await;
 
                     return;
-                }
-            }
-        }
+                };
+
+                // The device may not be in the list of discovered devices (e.g. if discovery is
+                // underway), in which case we fall back on where the device was last known to be.
+                let device_host = match self.devices.iter_mut().find(|device| device.udn == udn) {
+                    Some(device) => {
+                        device.is_activating = true;
+                        host_from_url(&device.url)
+                    }
+                    None => self.last_known_host(&udn),
+                };
+
+                let Some(host) = device_host else {
+                    send_app_log!(Error, "Could not determine device URL; not activating");
+                    self.transition_connection_state(ConnectionEvent::NoConnectionTarget);
+                    self.on_state_changed().await;
 
-        // Exponential backoff based on activation attempt count. Max out at 1500ms.
-        let base_delay = Duration::from_millis(100);
-        let max_delay = Duration::from_millis(1500);
+                    return;
+                };
 
-        let delay = match self.activation_attempts {
-            0 => Duration::from_millis(0),
-            _ => max_delay.min(base_delay * 2u32.pow(self.activation_attempts as u32)),
+                (host, Some(udn))
+            }
         };
 
-        self.activation_attempts += 1;
+        let attempt = match self.connection.state() {
+            ConnectionState::Activating(attempt) | ConnectionState::Reconnecting(attempt) => *attempt,
+            _ => 0,
+        };
 
         send_app_log!(
-            &self.tx_channel, Info,
-            "Activating device (attempt {} after {}ms delay), with UDN: {}",
-            self.activation_attempts, delay.as_millis(), &udn
+            Info,
+            "Activating device at {} (attempt {} after {}ms delay), with UDN: {}",
+            &host, attempt, delay.as_millis(), udn.as_deref().unwrap_or("unknown")
         );
 
-        sleep(delay).await;
-
-        self.set_all_device_activation_false().await;
+        // Ensure the UI knows about the new device state.
+        self.on_state_changed().await;
 
-        let matching_device = self.devices.iter_mut().find(|device| &*device.udn == udn);
-
-        // And open a WebSocketClient connection to the device. The device will be marked as active
-        // once the Connected WebSocketClient status is received.
-        if let Some(device) = matching_device {
-            if let Some(device_url) = host_from_url(&*device.url) {
-                device.is_activating = true;
-                self.on_state_changed().await;
-                self.start_websocket_client(&device_url).await;
-            } else {
-                send_app_log!(&self.tx_channel, Error, "Could not determine device URL; not activating");
-                self.set_is_activating(false).await;
-            }
+        if delay.is_zero() {
+            self.scheduled_connection = None;
+            self.start_websocket_client(&host).await;
+        } else {
+            self.scheduled_connection = Some((Instant::now() + delay, host));
         }
+    }
 
-        // Ensure the UI knows about the new device state.
-        self.emit_manager_state().await;
+    /// The UDN of the device being connected to: either the device being activated, or (when
+    /// reconnecting) the last-active device.
+    fn connection_target_udn(&self) -> Option<String> {
+        self.activating_udn
+            .clone()
+            .or_else(|| self.last_active_device.as_ref().map(|device| device.udn.clone()))
+    }
+
+    /// Where a device was last known to be, based on the last-active device or (failing that) the
+    /// device persisted by an earlier session. This ignores the discovered devices.
+    fn last_known_host(&self, udn: &str) -> Option<String> {
+        match &self.last_active_device {
+            Some(device) if device.udn == udn => host_from_url(&device.url),
+            _ if self.persisted_udn.as_deref() == Some(udn) => self.persisted_host.clone(),
+            _ => None,
+        }
     }
 
     async fn deactivate_active_device(&mut self) {
-        send_app_log!(&self.tx_channel, Info, "Deactivating currently-active device");
+        send_app_log!(Info, "Deactivating currently-active device");
 
         self.set_all_device_activation_false().await;
         self.stop_websocket_client().await;
+        self.handle_connection_event(ConnectionEvent::Stopped).await;
 
-        send_app_log!(&self.tx_channel, Info, "Deactivation complete");
+        send_app_log!(Info, "Deactivation complete");
 
         self.emit_manager_state().await;
     }
@@ -555,7 +1057,7 @@ impl StreamMagicManager {
     // WebSocketClient and StreamMagic device message handling
 
     async fn start_websocket_client(&mut self, host: &str) {
-        send_app_log!(&self.tx_channel, Info, "Initiating a new WebSocketClient connection to: {host}");
+        send_app_log!(Info, "Initiating a new WebSocketClient connection to: {host}");
 
         self.stop_websocket_client().await;
 
@@ -567,23 +1069,18 @@ impl StreamMagicManager {
         self.ws_client_receive_channel = Some(ws_msg_channel_rx);
 
         let streamer_url = format!("ws://{}:80/smoip", host);
-        let websocket_client_manager_channel = self.tx_channel.clone();
         let channel_clone = self.tx_channel.clone();
 
+        // The manager waits for this task to end when stopping the WebSocketClient, so the task
+        // can't wait for room in the manager's channel.
         self.ws_client_join_handle = Some(tauri::async_runtime::spawn(async move {
-            let mut ws_client = WebSocketClient::new(
-                &streamer_url,
-                websocket_client_manager_channel,
-                ws_cmd_channel_rx,
-                ws_msg_channel_tx,
-                false,
-            );
+            let mut ws_client = WebSocketClient::new(&streamer_url, ws_cmd_channel_rx, ws_msg_channel_tx);
 
             let ws_result = ws_client.run().await;
 
             if let Err(err) = &ws_result {
-                send_app_log!(&channel_clone, Warn, "StreamMagicManager detected client error: {:?}", &err);
-                send_manager_action!(&channel_clone, StreamMagicManagerAction::HandleClientError);
+                send_app_log!(Warn, "StreamMagicManager detected client error: {:?}", &err);
+                try_send_manager_action!(&channel_clone, StreamMagicManagerAction::HandleClientError);
             }
 
             ws_result
@@ -591,7 +1088,7 @@ impl StreamMagicManager {
     }
 
     async fn send_shutdown_request_to_websocket_client(&mut self) {
-        send_app_log!(&self.tx_channel, Info, "Sending ShutDown request to WebSocketClient");
+        send_app_log!(Info, "Sending ShutDown request to WebSocketClient");
 
         match &self.ws_client_send_channel {
             Some(ws_client_channel) => {
@@ -603,7 +1100,7 @@ impl StreamMagicManager {
                 {
                     Ok(_) => {}
                     Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn,
+                        send_app_log!(Warn,
                             "Could not send command {:?} to WebSocketClient: {:?}",
                             WebSocketClientAction::ShutDown, e
                         );
@@ -611,15 +1108,14 @@ impl StreamMagicManager {
                 }
             }
             None => {
-                send_app_log!(&self.tx_channel, Warn, "No WebSocketClient send channel found");
+                send_app_log!(Warn, "No WebSocketClient send channel found");
             }
         }
     }
 
-    async fn send_test_connection_request_to_websocket_client(&mut self) {
-        send_app_log!(&self.tx_channel, Debug, "Sending TestConnection request to WebSocketClient");
-        self.set_is_testing_connection(true).await;
-        self.count_of_disconnects_while_testing = 0;
+    /// Ask the WebSocketClient to test its connection. Returns whether the request was sent.
+    async fn send_test_connection_request_to_websocket_client(&mut self) -> bool {
+        send_app_log!(Debug, "Sending TestConnection request to WebSocketClient");
 
         match &self.ws_client_send_channel {
             Some(ws_client_channel) => {
@@ -629,39 +1125,39 @@ impl StreamMagicManager {
                     ))
                     .await
                 {
-                    Ok(_) => {}
+                    Ok(_) => true,
                     Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn,
+                        send_app_log!(Warn,
                             "Could not send command {:?} to WebSocketClient: {:?}",
                             WebSocketClientAction::TestConnection, e
                         );
-                        self.set_is_testing_connection(false).await;
+                        false
                     }
                 }
             }
             None => {
-                send_app_log!(&self.tx_channel, Warn, "No WebSocketClient send channel found");
-                self.set_is_testing_connection(false).await;
+                send_app_log!(Warn, "No WebSocketClient send channel found");
+                false
             }
         }
     }
 
     async fn stop_websocket_client(&mut self) {
         if let Some(handle) = self.ws_client_join_handle.take() {
-            send_app_log!(&self.tx_channel, Info, "Stopping existing WebSocketClient task");
+            send_app_log!(Info, "Stopping existing WebSocketClient task");
             self.send_shutdown_request_to_websocket_client().await;
 
             match handle.await {
                 Ok(ws_client_result) => match ws_client_result {
                     Ok(_) => {
-                        send_app_log!(&self.tx_channel, Info, "WebSocketClient successfully stopped");
+                        send_app_log!(Info, "WebSocketClient successfully stopped");
                     }
                     Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "WebSocketClient stopped with error: {:?}", e);
+                        send_app_log!(Warn, "WebSocketClient stopped with error: {:?}", e);
                     }
                 },
                 Err(e) => {
-                    send_app_log!(&self.tx_channel, Warn, "Could not stop WebSocketClient: {:?}", e);
+                    send_app_log!(Warn, "Could not stop WebSocketClient: {:?}", e);
                 }
             }
 
@@ -669,23 +1165,133 @@ impl StreamMagicManager {
         }
     }
 
-    async fn send_websocket_message(&mut self, msg: &str) {
+    async fn send_websocket_message(&mut self, request: &StreamMagicRequest) {
+        self.send_tracked_websocket_message(request, None).await;
+    }
+
+    /// Send a request to the Streamer, and track it until the Streamer responds. The optional
+    /// StreamerAction is the action which resulted in the request, and is used to report the
+    /// outcome of the action back to the UI.
+    async fn send_tracked_websocket_message(&mut self, request: &StreamMagicRequest, action: Option<StreamerAction>) {
+        let msg = match serde_json::to_string(request) {
+            Ok(msg) => msg,
+            Err(e) => {
+                send_app_log!(Warn, "Could not serialize {} request: {:?}", request.path(), e);
+                return;
+            }
+        };
+
         if let Some(send_channel) = &self.ws_client_send_channel {
-            match send_channel.send(WSClientRxChannelMsg::DataMsg(msg.to_string())).await {
-                Ok(()) => {}
+            match send_channel.send(WSClientRxChannelMsg::DataMsg(msg)).await {
+                Ok(()) => self.pending_requests.add(request.path(), action),
                 Err(e) => {
-                    send_app_log!(&self.tx_channel, Warn, "Could not send message to WebSocketClient channel: {:?}", e);
+                    send_app_log!(Warn, "Could not send message to WebSocketClient channel: {:?}", e);
                 }
             }
         }
     }
 
+    /// Handle a response received from the Streamer. The response resolves the oldest pending
+    /// request for the response's path. Failed StreamerActions are reported to the UI.
+    async fn process_streammagic_response(&mut self, message: &StreamMagicMessage) {
+        let Some(pending_request) = self.pending_requests.resolve(&message.path) else {
+            send_app_log!(
+                Debug, "Received {} response with no matching pending request", &message.path
+            );
+            return;
+        };
+
+        if message.result == 0 {
+            return;
+        }
+
+        if let Some(action) = pending_request.action {
+            send_app_log!(
+                Warn,
+                "Streamer rejected {:?} (result {}): {}",
+                &action, message.result, &message.message
+            );
+
+            self.emit_streamer_action_result(StreamerActionResult {
+                action,
+                result: Some(message.result),
+                message: message.message.clone(),
+                timed_out: false,
+            })
+            .await;
+        } else {
+            send_app_log!(
+                Warn,
+                "Streamer rejected {} request (result {}): {}",
+                &message.path, message.result, &message.message
+            );
+        }
+    }
+
+    /// Report pending StreamerActions which will never receive a response.
+    async fn report_unanswered_requests(&mut self, requests: Vec<PendingRequest>, reason: &str) {
+        for request in requests {
+            if let Some(action) = request.action {
+                send_app_log!(Warn, "{:?} ({}): {}", &action, &request.path, reason);
+
+                self.emit_streamer_action_result(StreamerActionResult {
+                    action,
+                    result: None,
+                    message: reason.to_string(),
+                    timed_out: true,
+                })
+                .await;
+            }
+        }
+    }
+
+    fn is_streamer_connected(&self) -> bool {
+        matches!(
+            self.ws_client_status,
+            WebSocketClientStatus::Connected(_) | WebSocketClientStatus::TestingConnection
+        )
+    }
+
+    /// Hold on to an action which can't be sent right now, for sending once the Streamer
+    /// connection is re-established. Actions are only queued while a connection is on its way.
+    async fn queue_streamer_action(&mut self, action: StreamerAction) {
+        if !self.connection.is_connecting() {
+            self.reject_streamer_action(action, "Not connected to a streamer").await;
+            return;
+        }
+
+        send_app_log!(Info, "Queueing {:?} until the streamer is connected", &action);
+
+        if let Some(dropped) = self.outgoing_queue.push(action, Instant::now()) {
+            self.report_unsent_actions(vec![dropped], "Too many actions queued while connecting").await;
+        }
+    }
+
+    /// Send any actions which were queued while there was no Streamer connection.
+    async fn send_queued_streamer_actions(&mut self) {
+        let expired = self.outgoing_queue.take_expired(Instant::now());
+        self.report_unsent_actions(expired, "Streamer connection was not established in time").await;
+
+        let queued_actions = self.outgoing_queue.take_all();
+
+        if !queued_actions.is_empty() {
+            send_app_log!(Info, "Sending {} queued action(s) to streamer", queued_actions.len());
+        }
+
+        for queued in queued_actions {
+            self.handle_streamer_action(queued.action).await;
+        }
+    }
+
     async fn register_for_streammagic_updates(&mut self) {
         self.send_websocket_message(&Presets::request_updates_msg()).await;
         self.send_websocket_message(&QueueInfo::request_updates_msg()).await;
+        self.send_websocket_message(&SystemDisplay::request_updates_msg()).await;
         self.send_websocket_message(&SystemInfo::request_updates_msg()).await;
         self.send_websocket_message(&SystemPower::request_updates_msg()).await;
         self.send_websocket_message(&SystemSources::request_updates_msg()).await;
+        self.send_websocket_message(&SystemUpdate::request_updates_msg()).await;
+        self.send_websocket_message(&ZoneAudio::request_updates_msg()).await;
         self.send_websocket_message(&ZoneNowPlaying::request_updates_msg())
             .await;
         self.send_websocket_message(&ZonePlayState::request_updates_msg()).await;
@@ -695,95 +1301,21 @@ impl StreamMagicManager {
 
     async fn process_streammagic_message(&mut self, message: &str) -> Result<(), PunyTunesError> {
         match serde_json::from_str::<StreamMagicMessage>(message) {
-            Ok(message) => match message.path.as_ref() {
-                "/queue/info" => match serde_json::from_value::<StreamerQueueInfo>(message.params) {
-                    Ok(payload) => {
-                        self.set_queue_info(payload).await;
-
-                        // A QueueInfo update is used to trigger a retrieval of the presumably
-                        // updated QueueList.
-                        self.send_websocket_message(&QueueList::request_state_msg()).await;
-                    }
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/queue/list" => match serde_json::from_value::<StreamerQueueList>(message.params) {
-                    Ok(payload) => self.set_queue_list(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/presets/list" => match serde_json::from_value::<StreamerPresets>(message.params) {
-                    Ok(payload) => self.set_presets(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/system/info" => match serde_json::from_value::<StreamerSystemInfo>(message.params) {
-                    Ok(payload) => self.set_system_info(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/system/power" => match serde_json::from_value::<StreamerSystemPower>(message.params) {
-                    Ok(payload) => self.set_system_power(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/system/sources" => match serde_json::from_value::<StreamerSystemSources>(message.params) {
-                    Ok(payload) => self.set_system_sources(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/zone/now_playing" => match serde_json::from_value::<StreamerZoneNowPlaying>(message.params) {
-                    Ok(payload) => self.set_zone_now_playing(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/zone/play_state" => match serde_json::from_value::<StreamerZonePlayState>(message.params) {
-                    Ok(payload) => self.set_zone_play_state(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                    }
-                },
-                "/zone/play_state/position" => match serde_json::from_value::<StreamerZonePosition>(message.params) {
-                    Ok(payload) => self.set_zone_position(payload).await,
-                    Err(e) => {
-                        // Position information is sometimes unavailable, which manifests here as
-                        // a missing "data" field in the message payload. We choose to ignore this
-                        // as it's not a major issue. Alternatively we could instead set a None
-                        // state for ZonePosition::position, but that doesn't seem useful (at the
-                        // time of writing this comment).
-                        let error_str = format!("{:?}", e);
-                        if !error_str.contains("missing field `data`") {
-                            send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
-                        }
-                    }
-                },
-                "/zone/state" => match serde_json::from_value::<StreamerZoneState>(message.params) {
-                    Ok(payload) => self.set_zone_state(payload).await,
-                    Err(e) => {
-                        send_app_log!(&self.tx_channel, Warn, "Could not decode {} payload: {:?}", &message.path, e);
+            Ok(message) => {
+                if message.payload_type == "response" {
+                    self.process_streammagic_response(&message).await;
+
+                    // A failed response won't carry a usable payload
+                    if message.result != 0 {
+                        return Ok(());
                     }
-                },
-                // We expect to receive a play_control message whenever we send a streamer action
-                // like "NextTrack"; but we don't need to do anything with it. Similarly we want
-                // to ignore /zone/recall_preset messages.
-                "/zone/play_control" | "/zone/recall_preset" => {}
-                unmatched => {
-                    // We shouldn't see this, if we're appropriately acting on any incoming message
-                    // path that we've actively subscribed to or that we're triggering as the result
-                    // of a streamer action.
-                    send_app_log!(&self.tx_channel, Warn, "Received unhandled StreamMagic message path: {unmatched}");
                 }
-            },
+
+                self.process_streammagic_payload(message).await;
+            }
             Err(e) => {
                 send_app_log!(
-                    &self.tx_channel, Warn,
+                    Warn,
                     "Could not deserialize StreamMagic message; error: {:?} :: message: {}",
                     e, &message
                 );
@@ -793,36 +1325,364 @@ impl StreamMagicManager {
         Ok(())
     }
 
+    async fn process_streammagic_payload(&mut self, message: StreamMagicMessage) {
+        match message.path.as_ref() {
+            "/queue/info" => match self.decode_payload::<StreamerQueueInfo>(&message) {
+                Ok(payload) => self.set_queue_info(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/queue/list" => match self.decode_payload::<StreamerQueueList>(&message) {
+                Ok(payload) => self.set_queue_list(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/presets/list" => match self.decode_payload::<StreamerPresets>(&message) {
+                Ok(payload) => self.set_presets(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/system/display" => match self.decode_payload::<StreamerSystemDisplay>(&message) {
+                Ok(payload) => self.set_system_display(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/system/info" => match self.decode_payload::<StreamerSystemInfo>(&message) {
+                Ok(payload) => self.set_system_info(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/system/power" => match self.decode_payload::<StreamerSystemPower>(&message) {
+                Ok(payload) => self.set_system_power(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/system/sources" => match self.decode_payload::<StreamerSystemSources>(&message) {
+                Ok(payload) => self.set_system_sources(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/system/update" => match self.decode_payload::<StreamerSystemUpdate>(&message) {
+                Ok(payload) => self.set_system_update(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/zone/audio" => match self.decode_payload::<StreamerZoneAudio>(&message) {
+                Ok(payload) => self.set_zone_audio(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/zone/now_playing" => match self.decode_payload::<StreamerZoneNowPlaying>(&message) {
+                Ok(payload) => self.set_zone_now_playing(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/zone/play_state" => match self.decode_payload::<StreamerZonePlayState>(&message) {
+                Ok(payload) => self.set_zone_play_state(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            "/zone/play_state/position" => match self.decode_payload::<StreamerZonePosition>(&message) {
+                Ok(payload) => self.set_zone_position(payload).await,
+                Err(e) => {
+                    // Position information is sometimes unavailable, which manifests here as
+                    // a missing "data" field in the message payload. We choose to ignore this
+                    // as it's not a major issue. Alternatively we could instead set a None
+                    // state for ZonePosition::position, but that doesn't seem useful (at the
+                    // time of writing this comment).
+                    let error_str = format!("{:?}", e);
+                    if !error_str.contains("missing field `data`") {
+                        send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                    }
+                }
+            },
+            "/zone/state" => match self.decode_payload::<StreamerZoneState>(&message) {
+                Ok(payload) => self.set_zone_state(payload).await,
+                Err(e) => {
+                    send_app_log!(Warn, "Could not decode {} payload: {:?}", &message.path, e);
+                }
+            },
+            // A successful queue edit is followed by a fresh QueueInfo, which triggers a retrieval
+            // of whichever queue items changed.
+            "/queue/add" | "/queue/clear" | "/queue/delete" | "/queue/move" => {
+                self.send_websocket_message(&QueueInfo::request_state_msg()).await;
+            }
+            // We expect to receive a play_control message whenever we send a streamer action
+            // like "NextTrack". Its result has already been handled as a response, so there's
+            // nothing else to do with it. The same goes for /zone/recall_preset messages, and for
+            // preset edits (the changed presets arrive as a /presets/list update) and radio streams
+            // (the new stream arrives as play state and now playing updates).
+            "/presets/delete"
+            | "/presets/rename"
+            | "/presets/store"
+            | "/stream/radio"
+            | "/zone/play_control"
+            | "/zone/recall_preset" => {}
+            unmatched => {
+                // We shouldn't see this, if we're appropriately acting on any incoming message
+                // path that we've actively subscribed to or that we're triggering as the result
+                // of a streamer action.
+                send_app_log!(Warn, "Received unhandled StreamMagic message path: {unmatched}");
+                self.unhandled_payloads.add_unhandled_path(unmatched, message.params.clone());
+            }
+        }
+
+        if self.unhandled_payloads.take_has_changed() {
+            self.emit_streammagic_payload(AppMessageType::StreamerUnhandledPayloads).await;
+        }
+    }
+
+    /// Decode a StreamMagic payload into its typed form. Any fields in the payload which the typed
+    /// form doesn't capture are retained for inspection in the UI.
+    fn decode_payload<T: serde::de::DeserializeOwned + serde::Serialize>(
+        &mut self,
+        message: &StreamMagicMessage,
+    ) -> Result<T, serde_json::Error> {
+        let payload = serde_json::from_value::<T>(message.params.clone())?;
+
+        self.unhandled_payloads.add_unknown_fields(&message.path, &message.params, || {
+            serde_json::to_value(&payload).ok()
+        });
+
+        Ok(payload)
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // StreamerAction handling
+
+    /// Check whether a StreamerAction can succeed given what we know of the Streamer's current
+    /// state, so that actions which are bound to fail are not sent.
+    fn validate_streamer_action(&self, action: &StreamerAction, zone: Option<&str>) -> Result<(), String> {
+        self.capabilities_for_zone(zone).check(action)?;
+
+        match action {
+            StreamerAction::PresetStore(_) if !self.is_presettable(zone) => {
+                Err("The current stream cannot be stored as a preset".to_string())
+            }
+            StreamerAction::SeekPercent(_) | StreamerAction::SeekRelative(_) => self
+                .track_duration(zone)
+                .map(|_| ())
+                .ok_or_else(|| "The current track does not have a known duration".to_string()),
+            StreamerAction::SetDisplayBrightness(DisplayBrightness::Unknown) => {
+                Err("The display brightness must be bright, dim, or off".to_string())
+            }
+            StreamerAction::SourceRename { id, .. } => match self.find_source(id)? {
+                source if !source.nameable => Err(format!("Source {} cannot be renamed", source.id)),
+                _ => Ok(()),
+            },
+            StreamerAction::SourceSetVisible { id, .. } => self.find_source(id).map(|_| ()),
+            StreamerAction::SourcesReorder(source_ids) => {
+                source_ids.iter().try_for_each(|source_id| self.find_source(source_id).map(|_| ()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn find_source(&self, source_id: &str) -> Result<&Source, String> {
+        self.system_sources
+            .as_ref()
+            .and_then(|sources| sources.system_sources().source(source_id))
+            .ok_or_else(|| format!("Unknown source: {}", source_id))
+    }
+
+    /// The current playback position (in seconds) in the given zone (or the default zone).
+    fn track_position(&self, zone: Option<&str>) -> i32 {
+        let Some(zone) = zone.or(self.default_zone()) else {
+            return 0;
+        };
+
+        self.zone_position
+            .get(zone)
+            .map(|position| position.position().position)
+            .or_else(|| self.zone_play_state.get(zone).and_then(|play_state| play_state.play_state().position))
+            .unwrap_or(0)
+    }
+
+    /// The duration (in seconds) of the track playing in the given zone (or the default zone). This
+    /// is None when the duration is not known (e.g. for radio streams).
+    fn track_duration(&self, zone: Option<&str>) -> Option<i32> {
+        let zone = zone.or(self.default_zone())?;
+
+        let play_state_duration = self
+            .zone_play_state
+            .get(zone)
+            .and_then(|play_state| play_state.play_state().metadata.as_ref())
+            .and_then(|metadata| metadata.duration);
+
+        let now_playing_duration = || {
+            self.zone_now_playing
+                .get(zone)
+                .and_then(|now_playing| now_playing.now_playing().display.as_ref())
+                .and_then(|display| display.progress.as_ref())
+                .and_then(|progress| progress.duration)
+        };
+
+        play_state_duration
+            .or_else(now_playing_duration)
+            .filter(|duration| *duration > 0)
+    }
+
+    /// Build the request which performs a StreamerAction in the given zone (or the default zone).
+    fn streamer_action_request(&self, action: &StreamerAction, zone: Option<&str>) -> StreamMagicRequest {
+        match action {
+            StreamerAction::InZone {
+                zone: action_zone,
+                action,
+            } => self
+                .streamer_action_request(action, zone.or(Some(action_zone.as_str())))
+                .with_zone(action_zone.clone()),
+            StreamerAction::MuteSet(is_muted) => ZoneState::set_mute_msg(*is_muted),
+            StreamerAction::NextTrack => PlayControl::next_track_msg(),
+            StreamerAction::Pause => PlayControl::pause_msg(),
+            StreamerAction::PlayAirableRadio(radio_id) => StreamRadio::play_airable_radio_id_msg(*radio_id),
+            StreamerAction::PlayQueueId(queue_id) => PlayControl::play_queue_id_msg(*queue_id),
+            StreamerAction::PlayPresetId(preset_id) => RecallPreset::play_preset_id_msg(*preset_id),
+            StreamerAction::PlayRadioUrl { url, name, art_url } => {
+                StreamRadio::play_url_msg(url.clone(), name.clone(), art_url.clone())
+            }
+            StreamerAction::PowerOn => SystemPower::on_msg(),
+            StreamerAction::PowerStandby => SystemPower::standby_msg(),
+            StreamerAction::PowerToggle => SystemPower::toggle_msg(),
+            StreamerAction::Play => PlayControl::play_msg(),
+            StreamerAction::PresetClear(preset_id) => PresetControl::clear_preset_id_msg(*preset_id),
+            StreamerAction::PresetRename { id, name } => PresetControl::rename_preset_id_msg(*id, name.clone()),
+            StreamerAction::PresetStore(preset_id) => PresetControl::store_preset_id_msg(*preset_id),
+            StreamerAction::PreviousTrack => PlayControl::previous_track_msg(),
+            StreamerAction::QueueAdd { url, title, position } => {
+                QueueControl::add_msg(url.clone(), title.clone(), position.clone())
+            }
+            StreamerAction::QueueClear => QueueControl::clear_msg(),
+            StreamerAction::QueueMove { id, position } => QueueControl::move_id_msg(*id, *position),
+            StreamerAction::QueueRemoveIds(ids) => QueueControl::remove_ids_msg(ids.clone()),
+            StreamerAction::Seek(position) => PlayControl::seek_msg(*position),
+            // Relative seeks are validated to have a known track duration before getting here
+            StreamerAction::SeekPercent(percent) => {
+                PlayControl::seek_percent_msg(*percent, self.track_duration(zone).unwrap_or_default())
+            }
+            StreamerAction::SeekRelative(offset) => PlayControl::seek_relative_msg(
+                self.track_position(zone),
+                *offset,
+                self.track_duration(zone).unwrap_or_default(),
+            ),
+            StreamerAction::SetBalance(balance) => ZoneAudio::set_balance_msg(*balance),
+            StreamerAction::SetControlBusMode(mode) => ZoneState::set_control_bus_mode_msg(mode.clone()),
+            StreamerAction::SetDisplayBrightness(brightness) => SystemDisplay::set_brightness_msg(brightness.clone()),
+            StreamerAction::SetPreAmpMode(is_enabled) => ZoneState::set_pre_amp_mode_msg(*is_enabled),
+            StreamerAction::SetRepeat(state) => PlayControl::set_repeat_msg(state.clone()),
+            StreamerAction::SetShuffle(state) => PlayControl::set_shuffle_msg(state.clone()),
+            StreamerAction::SetSourceId(source_id) => ZoneState::set_source_id_msg(source_id.clone()),
+            StreamerAction::SourceRename { id, name } => SystemSources::rename_source_msg(id.clone(), name.clone()),
+            StreamerAction::SourceSetVisible { id, is_visible } => {
+                SystemSources::set_source_visible_msg(id.clone(), *is_visible)
+            }
+            StreamerAction::SourcesReorder(source_ids) => SystemSources::reorder_sources_msg(source_ids.clone()),
+            StreamerAction::SetToneBass(bass) => ZoneAudio::set_tone_bass_msg(*bass),
+            StreamerAction::SetToneTreble(treble) => ZoneAudio::set_tone_treble_msg(*treble),
+            StreamerAction::SetUserEqBandGain { index, gain } => {
+                ZoneAudio::set_user_eq_band_gain_msg(*index, *gain)
+            }
+            StreamerAction::SetUserEqEnabled(is_enabled) => ZoneAudio::set_user_eq_enabled_msg(*is_enabled),
+            StreamerAction::Stop => PlayControl::stop_msg(),
+            StreamerAction::SystemUpdate(update_action) => SystemUpdate::action_msg(update_action.clone()),
+            StreamerAction::TogglePlayback => PlayControl::toggle_playback_msg(),
+            StreamerAction::VolumePercentSet(percent) => ZoneState::set_volume_percent_msg(*percent),
+            StreamerAction::VolumeStepChange(degree) => ZoneState::change_volume_step_msg(*degree),
+            StreamerAction::VolumeStepSet(step) => ZoneState::set_volume_step_msg(*step),
+        }
+    }
+
+    /// Send the request for a StreamerAction to the Streamer. The request is tracked until the
+    /// Streamer responds, so any failure can be reported back to the UI.
+    async fn handle_streamer_action(&mut self, action: StreamerAction) {
+        if !self.is_streamer_connected() {
+            self.queue_streamer_action(action).await;
+            return;
+        }
+
+        let validation = {
+            let (zone, zone_action) = action.zone_and_action();
+            self.validate_streamer_action(zone_action, zone)
+        };
+
+        if let Err(reason) = validation {
+            self.reject_streamer_action(action, &reason).await;
+            return;
+        }
+
+        let request = self.streamer_action_request(&action, None);
+
+        let (zone, zone_action) = action.zone_and_action();
+        let zone = zone.map(String::from);
+
+        let is_zone_mode_change = matches!(
+            zone_action,
+            StreamerAction::SetControlBusMode(_) | StreamerAction::SetPreAmpMode(_)
+        );
+
+        self.send_tracked_websocket_message(&request, Some(action)).await;
+
+        // Changing the pre-amp or Control Bus mode changes which volume fields the streamer
+        // reports, so follow up with a request for the complete ZoneState.
+        if is_zone_mode_change {
+            let request = match zone {
+                Some(zone) => ZoneState::request_state_msg().with_zone(zone),
+                None => ZoneState::request_state_msg(),
+            };
+
+            self.send_websocket_message(&request).await;
+        }
+    }
+
     // --------------------------------------------------------------------------------------------
 
     async fn initialize(&mut self) {
         // Auto-connect to the last-known host
 
-        let last_connected_host_maybe = self.get_persisted_backend_state_value(KEY_LAST_CONNECTED_HOST);
+        self.persisted_host = self
+            .get_persisted_backend_state_value(KEY_LAST_CONNECTED_HOST)
+            .and_then(|host| host.as_str().map(String::from));
+        self.persisted_udn = self
+            .get_persisted_backend_state_value(KEY_LAST_ACTIVATED_UDN)
+            .and_then(|udn| udn.as_str().map(String::from));
+
         let mut activate_discovered_device = false;
 
-        if let Some(last_connected_host) = last_connected_host_maybe {
+        if let Some(host) = self.persisted_host.clone() {
             send_app_log!(
-                &self.tx_channel,
                 Info,
-                "Using stored streamer host to initialize connection: {last_connected_host}"
+                "Using stored streamer host to initialize connection: {host} (UDN: {})",
+                self.persisted_udn.as_deref().unwrap_or("unknown")
             );
 
-            if let Some(host) = last_connected_host.as_str() {
-                let host_owned = host.to_owned();
-
-                // Treat this like an activation. We can't invoke activate_device() at this point
-                // as discovery has not yet completed, and we don't want to wait for discovery to
-                // complete as we'd like to connect ASAP if the last device is still on the network.
-                self.set_is_activating(true).await;
-                self.start_websocket_client(&host_owned.clone()).await;
+            // Treat this like an activation. Discovery has not yet completed, and we don't want to
+            // wait for it as we'd like to connect ASAP if the last device is still on the network.
+            // The persisted UDN's device isn't known yet, so its persisted host is connected to.
+            //
+            // If the persisted host no longer answers, then retries continue to be made against
+            // the persisted UDN. Once discovery finds that UDN (possibly at a new address), the
+            // device is reconnected to at its discovered address (see add_device()).
+            match self.persisted_udn.clone() {
+                Some(udn) => {
+                    self.activating_udn = Some(udn);
+                    self.handle_connection_event(ConnectionEvent::Activate).await;
+                }
+                None => self.activate_host(&host).await,
             }
         } else {
             activate_discovered_device = true; // Only auto-activate if there's no persisted host
         }
 
-        // TODO: Handle case where persisted host no longer exists (connection will fail, and it
-        //  would be nice to instead auto-connect to the first discovered device).
         self.do_discovery(activate_discovered_device).await;
     }
 
@@ -834,8 +1694,11 @@ impl StreamMagicManager {
         // Configure an interval which will always be checked regardless of whether there's any
         // items waiting in a channel for processing.
         let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut resume_detector = ResumeDetector::new(RESUME_THRESHOLD);
 
         loop {
+            let scheduled_connection_at = self.scheduled_connection.as_ref().map(|(at, _)| *at);
+
             select! {
                 // --------------------------------------------------------------------------------
                 // Incoming commands requested of the Manager. These commands will be coming from
@@ -852,7 +1715,7 @@ impl StreamMagicManager {
                                     },
                                     // TODO: Deprecate (replaced by ActivateUdn)
                                     StreamMagicManagerAction::ConnectToStreamer(host) => {
-                                        self.start_websocket_client(&host).await;
+                                        self.activate_host(&host).await;
                                     },
                                     StreamMagicManagerAction::Deactivate => {
                                         self.deactivate_active_device().await;
@@ -860,76 +1723,30 @@ impl StreamMagicManager {
                                     // TODO: Deprecate (replaced by Deactivate)
                                     StreamMagicManagerAction::DisconnectFromStreamer => {
                                         self.stop_websocket_client().await;
+                                        self.handle_connection_event(ConnectionEvent::Stopped).await;
                                     },
                                     StreamMagicManagerAction::Discover(activate) => {
                                         self.do_discovery(activate).await;
                                     },
-                                    StreamMagicManagerAction::EmitAppLog(app_log) => {
-                                        if self.ui_ready {
-                                            // We unpack the AppLog to pass it into emit_app_log(),
-                                            // where it gets repackaged back into an AppLog. This
-                                            // feels a little weird but we want to use emit_app_log
-                                            // (and not emit_app_message) as we want the standard
-                                            // Rust log entry to be emitted as well.
-                                            //
-                                            // The same happens in the handler below for
-                                            // StreamMagicManagerAction::OnUIReady.
-                                            self.app_handle.emit_app_log_with_target(
-                                                module_path!(),
-                                                app_log.level.into(),
-                                                &app_log.message
-                                            ).await;
-                                        } else {
-                                            // Log here too, just in case the UI is never ready and
-                                            // the buffered logs are never emitted.
-                                            info!("Buffering log: {:?}", app_log);
-
-                                            self.buffered_app_logs.push(app_log);
-                                        }
-                                    },
                                     StreamMagicManagerAction::HandleClientError => {
-                                        if self.is_testing_connection {
-                                            send_app_log!(&self.tx_channel, Warn, "Test resulted in client error");
-                                            self.set_is_testing_connection(false).await;
-                                        }
-
                                         // Consider all WebSocketClient-sourced PunyTunesErrors as potentially
-                                        // recoverable via a re-activation.
-                                        if let Some(last_device) = &self.last_active_device {
-                                            send_app_log!(&self.tx_channel, Info,"Client error; retrying last-active device");
-                                            let _ = &self.activate_device(&last_device.udn.clone()).await;
-                                        } else {
-                                            send_app_log!(
-                                                &self.tx_channel, Info, "Client error; not trying again (no last-active device)"
-                                            );
-                                        }
+                                        // recoverable via a re-activation (the state machine decides whether
+                                        // another attempt is made).
+                                        self.handle_connection_event(ConnectionEvent::ClientFailed).await;
                                     },
                                     StreamMagicManagerAction::OnUIReady => {
-                                        self.ui_ready = true;
                                         self.emit_manager_state().await;
                                         self.emit_streammagic_payloads().await;
-
-                                        for buffered_app_log in &self.buffered_app_logs {
-                                            let log_clone = buffered_app_log.clone();
-
-                                            self.app_handle.emit_app_log_with_target(
-                                                module_path!(),
-                                                log_clone.level.into(),
-                                                &log_clone.message
-                                            ).await;
-                                        }
-
-                                        self.buffered_app_logs.clear();
                                     },
                                     StreamMagicManagerAction::ProcessDiscoveredDevice(device) => {
-                                        send_app_log!(&self.tx_channel, Info, "Processing discovered device: {}", device);
+                                        send_app_log!(Info, "Processing discovered device: {}", device);
                                         self.add_device(device).await;
                                     },
                                     StreamMagicManagerAction::SetIsDiscovering(is_discovering) => {
                                         self.set_is_discovering(is_discovering).await;
                                     },
                                     StreamMagicManagerAction::ShutDown => {
-                                        send_app_log!(&self.tx_channel, Info, "StreamMagicManager shutdown requested");
+                                        send_app_log!(Info, "StreamMagicManager shutdown requested");
                                         self.stop_websocket_client().await;
 
                                         // TODO: Determine what to do about shutting down the StreamMagic
@@ -937,111 +1754,24 @@ impl StreamMagicManager {
                                         break;
                                     },
                                     StreamMagicManagerAction::StopWebSocketClient(remove_from_persisted_state) => {
-                                        send_app_log!(&self.tx_channel, Info, "StreamMagicManager WebSocket halt requested");
+                                        send_app_log!(Info, "StreamMagicManager WebSocket halt requested");
                                         self.stop_websocket_client().await;
                                         self.reset_websocket_related_state().await;
 
                                         if remove_from_persisted_state {
-                                            let persisted_backend_state = self.app_handle.state::<PersistedBackendState>();
-                                            let mut state_guard = persisted_backend_state.0.lock().unwrap();
-
-                                            if let Err(delete_error) = state_guard.delete(KEY_LAST_CONNECTED_HOST) {
-                                                send_app_log!(
-                                                    self.tx_channel, Warn,
-                                                    "Could not remove last connected host from persisted state: {:?}",
-                                                    delete_error
-                                                );
-                                            }
+                                            self.forget_persisted_device();
                                         }
                                     },
                                     StreamMagicManagerAction::TestConnection => {
-                                        match self.ws_client_status {
-                                            WebSocketClientStatus::Connected(_) => {
-                                                // When actively connected, ask the WebSocketClient to do a test
-                                                self.send_test_connection_request_to_websocket_client().await;
-                                            },
-                                            _ => {
-                                                // When not actively connected, attempt a reconnect to the last-active device
-                                                if let Some(last_device) = &self.last_active_device {
-                                                    send_app_log!(
-                                                        &self.tx_channel,
-                                                        Info,
-                                                        "No active WebSocketClient connection to test; attempting to re-activate last-seen device"
-                                                    );
-
-                                                    let _ = &self.activate_device(&last_device.udn.clone()).await;
-                                                } else {
-                                                    send_app_log!(
-                                                        &self.tx_channel,
-                                                        Warn,
-                                                        "Cannot perform test; no active WebSocketClient connection, and no known last-seen device to re-activate"
-                                                    );
-                                                }
-                                            }
-                                        }
+                                        // When actively connected, the WebSocketClient is asked to do a test.
+                                        // When not actively connected, a reconnect to the last-active device is
+                                        // attempted instead.
+                                        self.handle_connection_event(ConnectionEvent::TestRequested).await;
                                     },
                                 }
                             },
                             StreamMagicManagerChannelMsg::StreamerActionMsg(streamer_action) => {
-                                match streamer_action {
-                                    StreamerAction::MuteSet(is_muted) => {
-                                        self.send_websocket_message(&ZoneState::set_mute_msg(is_muted)).await;
-                                    },
-                                    StreamerAction::NextTrack => {
-                                        self.send_websocket_message(&PlayControl::next_track_msg()).await;
-                                    },
-                                    StreamerAction::Pause => {
-                                        self.send_websocket_message(&PlayControl::pause_msg()).await;
-                                    },
-                                    StreamerAction::PlayQueueId(queue_id) => {
-                                        self.send_websocket_message(&PlayControl::play_queue_id_msg(queue_id)).await;
-                                    },
-                                    StreamerAction::PlayPresetId(preset_id) => {
-                                        self.send_websocket_message(&RecallPreset::play_preset_id_msg(preset_id)).await;
-                                    },
-                                    StreamerAction::PowerOn => {
-                                        self.send_websocket_message(&SystemPower::on_msg()).await;
-                                    },
-                                    StreamerAction::PowerStandby => {
-                                        self.send_websocket_message(&SystemPower::standby_msg()).await;
-                                    },
-                                    StreamerAction::PowerToggle => {
-                                        self.send_websocket_message(&SystemPower::toggle_msg()).await;
-                                    },
-                                    StreamerAction::Play => {
-                                        self.send_websocket_message(&PlayControl::play_msg()).await;
-                                    },
-                                    StreamerAction::PreviousTrack => {
-                                        self.send_websocket_message(&PlayControl::previous_track_msg()).await;
-                                    },
-                                    StreamerAction::Seek(position) => {
-                                        self.send_websocket_message(&PlayControl::seek_msg(position)).await;
-                                    },
-                                    StreamerAction::SetRepeat(state) => {
-                                        self.send_websocket_message(&PlayControl::set_repeat_msg(state)).await;
-                                    },
-                                    StreamerAction::SetShuffle(state) => {
-                                        self.send_websocket_message(&PlayControl::set_shuffle_msg(state)).await;
-                                    },
-                                    StreamerAction::SetSourceId(source_id) => {
-                                        self.send_websocket_message(&ZoneState::set_source_id_msg(source_id)).await;
-                                    },
-                                    StreamerAction::Stop => {
-                                        self.send_websocket_message(&PlayControl::stop_msg()).await;
-                                    },
-                                    StreamerAction::TogglePlayback => {
-                                        self.send_websocket_message(&PlayControl::toggle_playback_msg()).await;
-                                    },
-                                    StreamerAction::VolumePercentSet(percent) => {
-                                        self.send_websocket_message(&ZoneState::set_volume_percent_msg(percent)).await;
-                                    },
-                                    StreamerAction::VolumeStepChange(degree) => {
-                                        self.send_websocket_message(&ZoneState::change_volume_step_msg(degree)).await;
-                                    },
-                                    StreamerAction::VolumeStepSet(step) => {
-                                        self.send_websocket_message(&ZoneState::set_volume_step_msg(step)).await;
-                                    },
-                                }
+                                self.handle_streamer_action(streamer_action).await;
                             },
                         }
                     }
@@ -1066,63 +1796,68 @@ impl StreamMagicManager {
                                 match self.process_streammagic_message(&message).await {
                                     Ok(_) => {},
                                     Err(e) => {
-                                        send_app_log!(&self.tx_channel, Warn, "Could not process StreamMagic message: {:?}", e);
-                                        send_app_log!(&self.tx_channel, Warn, "Unprocessable message: {}", &message);
+                                        send_app_log!(Warn, "Could not process StreamMagic message: {:?}", e);
+                                        send_app_log!(Warn, "Unprocessable message: {}", &message);
                                     },
                                 }
                             },
+                            WSClientTxChannelMsg::ConnectionQualityMsg(quality) => {
+                                self.set_connection_quality(quality).await;
+                            },
                             WSClientTxChannelMsg::WebSocketClientStatusMsg(status) => {
-                                if !self.is_testing_connection {
-                                    send_app_log!(&self.tx_channel, Info, "WebSocketClient status: {:?}", &status);
+                                let was_testing_connection = self.connection.state() == &ConnectionState::Testing;
+
+                                if !was_testing_connection {
+                                    send_app_log!(Info, "WebSocketClient status: {:?}", &status);
                                 }
 
                                 match &status {
                                     WebSocketClientStatus::Connected(details) => {
-                                        if self.is_testing_connection {
+                                        if was_testing_connection {
                                             // The WebSocketClient responds with a Connected state if it's been asked
                                             // to test the connection and the connection is OK.
-                                            send_app_log!(&self.tx_channel, Info, "Streamer connection OK");
-                                            self.set_is_testing_connection(false).await;
+                                            send_app_log!(Info, "Streamer connection OK");
                                         }
 
                                         if !details.existing {
                                             // This is a new connection not an existing one, so do some "we have a new
                                             // connection" tasks.
 
-                                            if let Some(host) = host_from_url(&details.url) {
-                                                // Persist this successful host for later use.
-                                                send_app_log!(&self.tx_channel, Info, "Persisting last connected host: {}", host);
-                                                let persisted_backend_state = self.app_handle.state::<PersistedBackendState>();
-                                                let mut state_guard = persisted_backend_state.0.lock().unwrap();
-
-                                                if let Err(set_error) = state_guard.set(KEY_LAST_CONNECTED_HOST, host.into()) {
-                                                    send_app_log!(
-                                                        self.tx_channel, Error,
-                                                        "Could not persist last connected host: {:?}",
-                                                        set_error
-                                                    );
-                                                }
-                                            }
-
                                             self.register_for_streammagic_updates().await;
 
                                             // Full QueueList details do not come in via updates.
-                                            // Instead, a request is made here -- and whenever a
-                                            // QueueInfo update is received.
-                                            self.send_websocket_message(&QueueList::request_state_msg()).await;
+                                            // Instead, the queue is retrieved (one page at a time)
+                                            // whenever a QueueInfo update is received -- starting
+                                            // with the QueueInfo which follows the registration.
+                                            self.queue_info = None;
+                                            self.queue_cache.reset();
+
+                                            // Zones are specific to the Streamer, so forget any
+                                            // zones from a previous connection.
+                                            self.clear_zone_payloads();
+                                            self.update_capabilities().await;
 
                                             // Whenever we get a Connected message for a non-existing connection, we
                                             // mark the device with the matching host name as active.
                                             self.set_device_is_active_from_url(&details.url).await;
+
+                                            // Persist this successful host for later use. This also persists the
+                                            // device's UDN if the device has been discovered.
+                                            if let Some(host) = host_from_url(&details.url) {
+                                                self.persist_connected_device(&host, None);
+                                            }
                                         }
 
-                                        self.set_is_activating(false).await;
+                                        self.handle_connection_event(ConnectionEvent::ClientConnected).await;
                                     },
                                     WebSocketClientStatus::Disconnected(details) => {
                                         if let Some(reason) = &details.reason {
-                                            send_app_log!(&self.tx_channel, Info, "WebSocketClient has disconnected with reason: {reason}");
+                                            send_app_log!(
+                                                Info,
+                                                "WebSocketClient has disconnected with reason: {reason}"
+                                            );
                                         } else {
-                                            send_app_log!(&self.tx_channel, Info, "WebSocketClient has disconnected with no message");
+                                            send_app_log!(Info, "WebSocketClient has disconnected with no message");
                                         }
 
                                         // If we get a Disconnected then we assume no devices are
@@ -1135,44 +1870,27 @@ impl StreamMagicManager {
                                         self.ws_client_send_channel = None;
                                         self.ws_client_receive_channel = None;
 
-                                        // If the connection is Disconnected and we were in test mode,
-                                        // then switch from test mode to (re)activation mode.
-                                        if self.is_testing_connection {
-                                             send_app_log!(&self.tx_channel, Warn, "Test resulted in Disconnect");
-                                             self.set_is_testing_connection(false).await;
-
-                                             if let Some(last_active_device) = &self.last_active_device {
-                                                 send_app_log!(
-                                                     &self.tx_channel, Info, "Attempting re-activation of {}", &last_active_device.udn
-                                                 );
-                                                 self.activate_device(&last_active_device.udn.clone()).await;
-                                            } else {
-                                                send_app_log!(
-                                                    &self.tx_channel, Info,
-                                                    "Giving up on connection test (no last-active device to reactivate)"
-                                                );
-                                            }
+                                        // Nothing sent on the lost connection will receive a reply.
+                                        let unanswered_requests = self.pending_requests.take_all();
+                                        self.report_unanswered_requests(
+                                            unanswered_requests, "Streamer connection lost before a reply was received"
+                                        ).await;
+
+                                        if was_testing_connection {
+                                            send_app_log!(Warn, "Test resulted in Disconnect");
                                         } else if details.consider_reconnecting {
-                                            send_app_log!(&self.tx_channel, Info, "Potentially-recoverable disconnect");
-
-                                            if let Some(last_active_device) = &self.last_active_device {
-                                                send_app_log!(
-                                                    &self.tx_channel, Info, "Attempting re-activation of {}", &last_active_device.udn
-                                                );
-                                                self.activate_device(&last_active_device.udn.clone()).await;
-                                            } else {
-                                                send_app_log!(
-                                                    &self.tx_channel, Warn,
-                                                    "Unable to determine last active UDN; not attempting reconnect"
-                                                );
-                                                self.set_is_activating(false).await;
-                                            }
-                                        } else {
-                                            self.set_is_activating(false).await;
+                                            send_app_log!(Info, "Potentially-recoverable disconnect");
                                         }
+
+                                        // A disconnect during a test, or a potentially-recoverable disconnect,
+                                        // switches to reconnection mode. The reconnection attempt itself is
+                                        // made once the WebSocketClient reports its error.
+                                        self.handle_connection_event(ConnectionEvent::ClientDisconnected {
+                                            consider_reconnecting: details.consider_reconnecting,
+                                        }).await;
                                     },
                                     WebSocketClientStatus::TestingConnection => {
-                                        send_app_log!(&self.tx_channel, Debug, "WebSocketClient is testing its connection");
+                                        send_app_log!(Debug, "WebSocketClient is testing its connection");
                                     },
                                     _ => {},
                                 }
@@ -1180,20 +1898,73 @@ impl StreamMagicManager {
                                 // Update the manager's tracking of the client status (which will
                                 // also update the UI).
                                 self.set_websocket_client_status(&status).await;
+
+                                if let WebSocketClientStatus::Connected(_) = status {
+                                    self.send_queued_streamer_actions().await;
+                                }
                             },
                         }
+                    } else {
+                        // The WebSocketClient ended without reporting a disconnect (e.g. when it
+                        // failed to connect). Its channel will never receive again, so stop
+                        // checking it (otherwise recv() returns None on every loop iteration).
+                        self.ws_client_receive_channel = None;
                     }
                 }
 
+                // --------------------------------------------------------------------------------
+                // A delayed connection attempt is due.
+                _ = sleep_until(scheduled_connection_at.unwrap_or_else(Instant::now).into()),
+                    if scheduled_connection_at.is_some() => {
+                    if let Some((_, host)) = self.scheduled_connection.take() {
+                        self.start_websocket_client(&host).await;
+                    }
+                },
+
                 // --------------------------------------------------------------------------------
                 // Do some checks every interval, regardless of incoming messages.
                 _ = interval.tick() => {
+                    let now = Instant::now();
+
+                    // The connection may not have survived the machine sleeping, so test it now
+                    // rather than waiting for the WebSocketClient to notice.
+                    if let Some(gap) = resume_detector.tick(now, SystemTime::now()) {
+                        send_app_log!(
+                            Info,
+                            "Resumed after {}s; testing streamer connection",
+                            gap.as_secs()
+                        );
+                        self.handle_connection_event(ConnectionEvent::TestRequested).await;
+                    }
+
+                    if self.connection.is_connecting() {
+                        self.handle_connection_event(ConnectionEvent::Tick).await;
+                    }
+
+                    let expired_requests = self.pending_requests.take_expired(now);
+                    self.report_unanswered_requests(expired_requests, "No reply received from streamer").await;
+
+                    // Request any queue pages which never arrived
+                    if self.is_streamer_connected() {
+                        let pages = self.queue_cache.pages_to_request(now);
+                        self.request_queue_pages(pages).await;
+                    }
+
+                    // Queued actions are only kept while a connection is being established.
+                    if self.connection.is_connecting() {
+                        let expired_actions = self.outgoing_queue.take_expired(now);
+                        let reason = "Streamer connection was not established in time";
+                        self.report_unsent_actions(expired_actions, reason).await;
+                    } else if !self.is_streamer_connected() {
+                        let abandoned_actions = self.outgoing_queue.take_all();
+                        self.report_unsent_actions(abandoned_actions, "Streamer connection attempts stopped").await;
+                    }
                 },
             }
         }
 
         // We don't expect to get here unless the application is ending.
-        send_app_log!(&self.tx_channel, Info, "StreamMagicManager has ended");
+        send_app_log!(Info, "StreamMagicManager has ended");
 
         Ok(())
     }
@@ -0,0 +1,138 @@
+//! Describes what the connected streamer can currently do.
+//!
+//! Capabilities are derived from the `controls` listed in the zone's `ZoneNowPlaying` payload. The
+//! controls change with the active source (e.g. a radio stream can't be seeked or skipped), so
+//! capabilities are recomputed whenever the payload changes.
+//!
+//! Transport actions are only gated once the streamer has told us which controls are available.
+//! Play is never gated, as it's used to start playback from a stopped state (where the controls
+//! may be empty).
+//!
+//! `SystemInfo.api` and `SystemInfo.model` are deliberately not used. The StreamMagic API doesn't
+//! document which models or API versions support which actions, and the actions which do vary by
+//! source are already covered by `controls`. Gating on model or API version would mean guessing,
+//! and a wrong guess would block actions the streamer supports. Actions which a streamer doesn't
+//! support are instead rejected by the streamer itself, and reported as failed requests.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::streammagic_manager::StreamerAction;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/StreamerCapabilities.ts")]
+pub struct StreamerCapabilities {
+    // Whether the streamer has reported its available controls. Until it has, transport actions
+    // are assumed to be supported.
+    pub controls_known: bool,
+    pub can_pause: bool,
+    pub can_play_next: bool,
+    pub can_play_previous: bool,
+    pub can_seek: bool,
+    pub can_stop: bool,
+    pub can_toggle_playback: bool,
+    pub can_toggle_repeat: bool,
+    pub can_toggle_shuffle: bool,
+}
+
+impl StreamerCapabilities {
+    pub fn new(controls: Option<&[String]>) -> Self {
+        let has_control = |control: &str| controls.is_some_and(|controls| controls.iter().any(|c| c == control));
+
+        StreamerCapabilities {
+            controls_known: controls.is_some(),
+            can_pause: has_control("pause"),
+            can_play_next: has_control("track_next"),
+            can_play_previous: has_control("track_previous"),
+            can_seek: has_control("seek"),
+            can_stop: has_control("stop"),
+            can_toggle_playback: has_control("play_pause"),
+            can_toggle_repeat: has_control("toggle_repeat"),
+            can_toggle_shuffle: has_control("toggle_shuffle"),
+        }
+    }
+
+    /// Check whether an action is supported by the streamer. The error describes why the action
+    /// is not supported.
+    pub fn check(&self, action: &StreamerAction) -> Result<(), String> {
+        if !self.controls_known {
+            return Ok(());
+        }
+
+        let (is_supported, description) = match action {
+            StreamerAction::NextTrack => (self.can_play_next, "skipping to the next track"),
+            // The streamer accepts a pause when it can toggle playback
+            StreamerAction::Pause => (self.can_pause || self.can_toggle_playback, "pausing"),
+            StreamerAction::PreviousTrack => (self.can_play_previous, "skipping to the previous track"),
+            StreamerAction::Seek(_) | StreamerAction::SeekPercent(_) | StreamerAction::SeekRelative(_) => {
+                (self.can_seek, "seeking")
+            }
+            StreamerAction::SetRepeat(_) => (self.can_toggle_repeat, "changing repeat"),
+            StreamerAction::SetShuffle(_) => (self.can_toggle_shuffle, "changing shuffle"),
+            StreamerAction::Stop => (self.can_stop, "stopping"),
+            StreamerAction::TogglePlayback => (self.can_toggle_playback, "toggling playback"),
+            _ => (true, ""),
+        };
+
+        if is_supported {
+            Ok(())
+        } else {
+            Err(format!("The current source does not support {}", description))
+        }
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streammagic_manager::payloads::TransportToggleState;
+
+    fn controls(controls: &[&str]) -> Vec<String> {
+        controls.iter().map(|control| control.to_string()).collect()
+    }
+
+    #[test]
+    fn test_derives_capabilities_from_controls() {
+        let controls = controls(&["pause", "track_next", "seek"]);
+        let capabilities = StreamerCapabilities::new(Some(&controls));
+
+        assert!(capabilities.controls_known);
+        assert!(capabilities.can_pause);
+        assert!(capabilities.can_play_next);
+        assert!(capabilities.can_seek);
+        assert!(!capabilities.can_play_previous);
+        assert!(!capabilities.can_toggle_playback);
+        assert!(!capabilities.can_toggle_repeat);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_actions() {
+        let controls = controls(&["play_pause"]);
+        let capabilities = StreamerCapabilities::new(Some(&controls));
+
+        assert!(capabilities.check(&StreamerAction::TogglePlayback).is_ok());
+        assert!(capabilities.check(&StreamerAction::Pause).is_ok());
+        assert!(capabilities.check(&StreamerAction::Play).is_ok());
+        assert!(capabilities.check(&StreamerAction::VolumeStepChange(1)).is_ok());
+
+        assert!(capabilities.check(&StreamerAction::NextTrack).is_err());
+        assert!(capabilities.check(&StreamerAction::Stop).is_err());
+        assert!(capabilities.check(&StreamerAction::Seek(10)).is_err());
+        assert!(capabilities.check(&StreamerAction::SeekRelative(-15)).is_err());
+        assert!(capabilities.check(&StreamerAction::SetShuffle(TransportToggleState::All)).is_err());
+    }
+
+    #[test]
+    fn test_allows_all_actions_until_controls_are_known() {
+        let capabilities = StreamerCapabilities::new(None);
+
+        assert!(!capabilities.controls_known);
+        assert!(capabilities.check(&StreamerAction::NextTrack).is_ok());
+        assert!(capabilities.check(&StreamerAction::Seek(10)).is_ok());
+        assert!(capabilities.check(&StreamerAction::Stop).is_ok());
+    }
+}
@@ -0,0 +1,228 @@
+//! Measures the health of a WebSocket connection using client-initiated keepalive pings.
+//!
+//! The WebSocketClient sends a keepalive ping at a regular interval. Each ping's payload carries
+//! a sequence number, which the server echoes back in its pong. This allows each pong to be
+//! matched to its ping to calculate the round-trip latency, and allows pings which never receive
+//! a pong to be counted as lost. Several consecutive lost pings suggest that the connection is
+//! dead, even if the socket itself hasn't reported an error (e.g. after the machine has slept).
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ts_rs::TS;
+
+use crate::average::RunningAverage;
+
+const PING_PAYLOAD_PREFIX: &str = "keepalive-";
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/ConnectionHealth.ts")]
+pub enum ConnectionHealth {
+    // No latency measurements yet
+    #[default]
+    Unknown,
+    Good,
+    // Slow responses, or a recently-lost ping
+    Degraded,
+    // Multiple consecutive pings have been lost
+    Poor,
+}
+
+/// Connection quality details which might be of interest to the UI.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/ConnectionQuality.ts")]
+pub struct ConnectionQuality {
+    pub health: ConnectionHealth,
+    // Round-trip latencies (in milliseconds) over the recent pings
+    pub latency_avg_ms: Option<f64>,
+    pub latency_p95_ms: Option<f64>,
+    pub pings_sent: usize,
+    pub pings_lost: usize,
+    pub consecutive_pings_lost: usize,
+}
+
+pub struct KeepaliveTracker {
+    next_sequence: u64,
+    // Pings which haven't yet received a pong, oldest first
+    awaiting_pong: VecDeque<(u64, Instant)>,
+    latencies_ms: RunningAverage,
+    pong_timeout: Duration,
+    pings_sent: usize,
+    pings_lost: usize,
+    consecutive_pings_lost: usize,
+}
+
+impl KeepaliveTracker {
+    pub fn new(latency_window: usize, pong_timeout: Duration) -> Self {
+        KeepaliveTracker {
+            next_sequence: 0,
+            awaiting_pong: VecDeque::new(),
+            latencies_ms: RunningAverage::new(latency_window),
+            pong_timeout,
+            pings_sent: 0,
+            pings_lost: 0,
+            consecutive_pings_lost: 0,
+        }
+    }
+
+    pub fn consecutive_pings_lost(&self) -> usize {
+        self.consecutive_pings_lost
+    }
+
+    /// Record a new ping, returning the payload to send with it.
+    pub fn ping(&mut self, now: Instant) -> Vec<u8> {
+        let sequence = self.next_sequence;
+
+        self.next_sequence += 1;
+        self.pings_sent += 1;
+        self.awaiting_pong.push_back((sequence, now));
+
+        format!("{PING_PAYLOAD_PREFIX}{sequence}").into_bytes()
+    }
+
+    /// Record a received pong. Returns false if the pong isn't for a keepalive ping which is
+    /// still awaiting its pong (e.g. a connection test pong, or a pong which arrived too late).
+    pub fn pong(&mut self, payload: &[u8], now: Instant) -> bool {
+        let Some(sequence) = std::str::from_utf8(payload)
+            .ok()
+            .and_then(|payload| payload.strip_prefix(PING_PAYLOAD_PREFIX))
+            .and_then(|sequence| sequence.parse::<u64>().ok())
+        else {
+            return false;
+        };
+
+        let Some(index) = self.awaiting_pong.iter().position(|(pinged, _)| *pinged == sequence) else {
+            return false;
+        };
+
+        if let Some((_, sent_at)) = self.awaiting_pong.remove(index) {
+            self.latencies_ms.add(now.duration_since(sent_at).as_secs_f64() * 1000.0);
+        }
+
+        self.consecutive_pings_lost = 0;
+
+        true
+    }
+
+    /// Count pings which have waited longer than the pong timeout as lost. Returns whether any
+    /// pings were newly lost.
+    pub fn check_lost(&mut self, now: Instant) -> bool {
+        let mut lost_count = 0;
+
+        while let Some((_, sent_at)) = self.awaiting_pong.front() {
+            if now.duration_since(*sent_at) <= self.pong_timeout {
+                break;
+            }
+
+            self.awaiting_pong.pop_front();
+            lost_count += 1;
+        }
+
+        self.pings_lost += lost_count;
+        self.consecutive_pings_lost += lost_count;
+
+        lost_count > 0
+    }
+
+    pub fn quality(&self) -> ConnectionQuality {
+        let (latency_avg_ms, latency_p95_ms) = match self.latencies_ms.len() {
+            0 => (None, None),
+            _ => (Some(self.latencies_ms.average()), Some(percentile(&self.latencies_ms.values, 95.0))),
+        };
+
+        let health = match (self.consecutive_pings_lost, latency_p95_ms) {
+            (lost, _) if lost > 1 => ConnectionHealth::Poor,
+            (1, _) => ConnectionHealth::Degraded,
+            (_, Some(p95)) if p95 > 500.0 => ConnectionHealth::Degraded,
+            (_, Some(_)) => ConnectionHealth::Good,
+            (_, None) => ConnectionHealth::Unknown,
+        };
+
+        ConnectionQuality {
+            health,
+            latency_avg_ms,
+            latency_p95_ms,
+            pings_sent: self.pings_sent,
+            pings_lost: self.pings_lost,
+            consecutive_pings_lost: self.consecutive_pings_lost,
+        }
+    }
+}
+
+/// The nearest-rank percentile of the given values. Expects at least one value.
+fn percentile(values: &[f64], percentile: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<f64> = (1..=20).map(|value| value as f64).collect();
+
+        assert_eq!(percentile(&values, 95.0), 19.0);
+        assert_eq!(percentile(&values, 100.0), 20.0);
+        assert_eq!(percentile(&[7.0], 95.0), 7.0);
+    }
+
+    #[test]
+    fn test_measures_latency() {
+        let mut tracker = KeepaliveTracker::new(10, Duration::from_secs(3));
+        let now = Instant::now();
+
+        assert_eq!(tracker.quality().health, ConnectionHealth::Unknown);
+
+        let first = tracker.ping(now);
+        let second = tracker.ping(now + Duration::from_secs(5));
+
+        assert!(tracker.pong(&first, now + Duration::from_millis(20)));
+        assert!(tracker.pong(&second, now + Duration::from_millis(5040)));
+
+        // Pongs which aren't for outstanding keepalive pings are ignored
+        assert!(!tracker.pong(&first, now + Duration::from_secs(6)));
+        assert!(!tracker.pong(b"PING", now + Duration::from_secs(6)));
+
+        let quality = tracker.quality();
+        assert_eq!(quality.health, ConnectionHealth::Good);
+        assert_eq!(quality.latency_avg_ms, Some(30.0));
+        assert_eq!(quality.latency_p95_ms, Some(40.0));
+        assert_eq!(quality.pings_sent, 2);
+        assert_eq!(quality.pings_lost, 0);
+    }
+
+    #[test]
+    fn test_counts_lost_pings() {
+        let mut tracker = KeepaliveTracker::new(10, Duration::from_secs(3));
+        let now = Instant::now();
+
+        tracker.ping(now);
+        assert!(!tracker.check_lost(now + Duration::from_secs(3)));
+        assert!(tracker.check_lost(now + Duration::from_secs(4)));
+        assert_eq!(tracker.quality().health, ConnectionHealth::Degraded);
+
+        tracker.ping(now + Duration::from_secs(5));
+        assert!(tracker.check_lost(now + Duration::from_secs(9)));
+        assert_eq!(tracker.consecutive_pings_lost(), 2);
+        assert_eq!(tracker.quality().health, ConnectionHealth::Poor);
+
+        // A pong resets the consecutive loss count, but not the overall loss count
+        let payload = tracker.ping(now + Duration::from_secs(10));
+        tracker.pong(&payload, now + Duration::from_millis(10100));
+
+        let quality = tracker.quality();
+        assert_eq!(quality.consecutive_pings_lost, 0);
+        assert_eq!(quality.pings_lost, 2);
+        assert_eq!(quality.health, ConnectionHealth::Good);
+    }
+}
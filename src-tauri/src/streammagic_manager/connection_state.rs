@@ -0,0 +1,419 @@
+//! The StreamMagicManager's connection state machine.
+//!
+//! All connection-related state (discovery, activation, connection tests, and reconnection) is
+//! tracked here as a single `ConnectionState`, rather than being spread across the manager. The
+//! manager reports what's happening as `ConnectionEvent`s, and the state machine replies with the
+//! `ConnectionEffect` (if any) the manager should carry out. The state machine itself doesn't
+//! perform any I/O, which allows every transition to be unit tested.
+//!
+//! Transitions:
+//!
+//! ```text
+//!   Idle/Discovering/GivingUp --Activate--------------------------> Activating
+//!   Idle/Discovering/GivingUp --TestRequested---------------------> Reconnecting
+//!   Activating/Reconnecting ----ClientConnected-------------------> Connected
+//!   Activating/Reconnecting ----ClientFailed----------------------> (same state, next attempt)
+//!   Activating/Reconnecting ----Tick (background attempt due)-----> (same state, next attempt)
+//!   Connected ------------------TestRequested---------------------> Testing
+//!   Testing --------------------ClientConnected-------------------> Connected
+//!   Connected/Testing ----------ClientFailed----------------------> Reconnecting
+//!   Connected/Testing ----------ClientDisconnected (recoverable)--> Reconnecting
+//!   Any ------------------------ClientDisconnected (unrecoverable)> Idle/Discovering
+//!   Any ------------------------Stopped---------------------------> Idle/Discovering
+//!   Any ------------------------NoConnectionTarget----------------> GivingUp
+//! ```
+//!
+//! A recoverable disconnect is always followed by the WebSocketClient failing, so the disconnect
+//! only moves the state to Reconnecting. The next connection attempt is made on `ClientFailed`,
+//! which ensures a single lost connection results in a single reconnection attempt.
+//!
+//! The timing of connection attempts is left to a `ReconnectPolicy`. Fast attempts result in a
+//! `Connect` effect with a (short) delay, whereas background attempts are made on the first `Tick`
+//! after they're due.
+
+use std::time::{Duration, Instant};
+
+use ts_rs::TS;
+
+use crate::reconnect_policy::{ReconnectPolicy, ReconnectPolicyConfig, ReconnectState};
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, TS)]
+#[serde(tag = "state", content = "metadata")]
+#[ts(export, export_to = "../src/types/generated/ConnectionState.ts")]
+pub enum ConnectionState {
+    // Not connected, and not attempting to connect
+    Idle,
+    // Discovering devices, while not connected or attempting to connect
+    Discovering,
+    // Connecting to a newly-activated device. usize is the number of attempts made.
+    Activating(usize),
+    Connected,
+    // Checking whether the connection is still alive
+    Testing,
+    // Connecting to the previously-connected device. usize is the number of attempts made.
+    Reconnecting(usize),
+    // There's nothing to connect to. String is the reason.
+    GivingUp(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    DiscoveryStarted,
+    DiscoveryEnded,
+    // A device activation was requested (by the user, or on startup)
+    Activate,
+    // The WebSocketClient has connected (or has passed a connection test)
+    ClientConnected,
+    ClientDisconnected { consider_reconnecting: bool },
+    // The WebSocketClient failed to connect, or ended with an error
+    ClientFailed,
+    TestRequested,
+    // There's no known device to connect to
+    NoConnectionTarget,
+    // The connection was deliberately stopped
+    Stopped,
+    // Periodic check (e.g. once a second) while connecting
+    Tick,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionEffect {
+    None,
+    // Connect to the device being (re)activated, after the given delay
+    Connect(Duration),
+    // Ask the WebSocketClient to test its connection
+    Test,
+}
+
+pub struct ConnectionStateMachine {
+    state: ConnectionState,
+    is_discovering: bool,
+    // When to make each activation/reconnection attempt
+    reconnect: ReconnectPolicy,
+}
+
+impl ConnectionStateMachine {
+    pub fn new(reconnect_config: ReconnectPolicyConfig) -> Self {
+        ConnectionStateMachine {
+            state: ConnectionState::Idle,
+            is_discovering: false,
+            reconnect: ReconnectPolicy::new(reconnect_config),
+        }
+    }
+
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    pub fn is_discovering(&self) -> bool {
+        self.is_discovering
+    }
+
+    pub fn reconnect_state(&self) -> ReconnectState {
+        self.reconnect.state()
+    }
+
+    /// Whether a connection is being established (by an activation or a reconnection).
+    pub fn is_connecting(&self) -> bool {
+        matches!(self.state, ConnectionState::Activating(_) | ConnectionState::Reconnecting(_))
+    }
+
+    /// Transition to the next state, returning what (if anything) needs to be done as a result.
+    pub fn handle(&mut self, event: ConnectionEvent, now: Instant) -> ConnectionEffect {
+        match event {
+            ConnectionEvent::DiscoveryStarted => {
+                self.is_discovering = true;
+
+                if self.is_resting() {
+                    self.state = ConnectionState::Discovering;
+                }
+
+                ConnectionEffect::None
+            }
+            ConnectionEvent::DiscoveryEnded => {
+                self.is_discovering = false;
+
+                if self.state == ConnectionState::Discovering {
+                    self.state = ConnectionState::Idle;
+                }
+
+                ConnectionEffect::None
+            }
+            ConnectionEvent::Activate => {
+                self.reconnect.reset();
+                self.state = ConnectionState::Activating(0);
+
+                self.next_attempt(now)
+            }
+            ConnectionEvent::ClientConnected => {
+                self.reconnect.reset();
+                self.state = ConnectionState::Connected;
+
+                ConnectionEffect::None
+            }
+            ConnectionEvent::ClientDisconnected { consider_reconnecting } => {
+                match self.state {
+                    // A test which results in a disconnect is always considered recoverable
+                    ConnectionState::Testing => self.start_reconnecting(),
+                    ConnectionState::Connected if consider_reconnecting => self.start_reconnecting(),
+                    ConnectionState::Connected => self.rest(),
+                    ConnectionState::Activating(_) | ConnectionState::Reconnecting(_) if !consider_reconnecting => {
+                        self.rest()
+                    }
+                    _ => {}
+                }
+
+                ConnectionEffect::None
+            }
+            ConnectionEvent::ClientFailed => match self.state {
+                ConnectionState::Activating(_) | ConnectionState::Reconnecting(_) => self.next_attempt(now),
+                ConnectionState::Connected | ConnectionState::Testing => {
+                    self.start_reconnecting();
+                    self.next_attempt(now)
+                }
+                _ => ConnectionEffect::None,
+            },
+            ConnectionEvent::TestRequested => match self.state {
+                ConnectionState::Connected => {
+                    self.state = ConnectionState::Testing;
+                    ConnectionEffect::Test
+                }
+                // A test or connection attempt is already in progress
+                ConnectionState::Testing | ConnectionState::Activating(_) | ConnectionState::Reconnecting(_) => {
+                    ConnectionEffect::None
+                }
+                // There's no connection to test, so attempt to reconnect instead
+                _ => {
+                    self.start_reconnecting();
+                    self.next_attempt(now)
+                }
+            },
+            ConnectionEvent::NoConnectionTarget => {
+                self.give_up("No known device to connect to".to_string());
+                ConnectionEffect::None
+            }
+            ConnectionEvent::Stopped => {
+                self.rest();
+                ConnectionEffect::None
+            }
+            ConnectionEvent::Tick => {
+                if self.is_connecting() && self.reconnect.take_due_background_attempt(now) {
+                    ConnectionEffect::Connect(Duration::ZERO)
+                } else {
+                    ConnectionEffect::None
+                }
+            }
+        }
+    }
+
+    /// Whether nothing connection-related is happening.
+    fn is_resting(&self) -> bool {
+        matches!(
+            self.state,
+            ConnectionState::Idle | ConnectionState::Discovering | ConnectionState::GivingUp(_)
+        )
+    }
+
+    fn rest(&mut self) {
+        self.reconnect.reset();
+        self.state = match self.is_discovering {
+            true => ConnectionState::Discovering,
+            false => ConnectionState::Idle,
+        };
+    }
+
+    fn give_up(&mut self, reason: String) {
+        self.reconnect.reset();
+        self.state = ConnectionState::GivingUp(reason);
+    }
+
+    fn start_reconnecting(&mut self) {
+        self.reconnect.reset();
+        self.state = ConnectionState::Reconnecting(0);
+    }
+
+    /// Schedule another connection attempt. Fast attempts are made (after a delay) straight away,
+    /// whereas background attempts wait for a Tick.
+    fn next_attempt(&mut self, now: Instant) -> ConnectionEffect {
+        let delay = self.reconnect.schedule_next(now);
+        let attempts = self.reconnect.attempts();
+
+        self.state = match self.state {
+            ConnectionState::Activating(_) => ConnectionState::Activating(attempts),
+            ConnectionState::Reconnecting(_) => ConnectionState::Reconnecting(attempts),
+            _ => return ConnectionEffect::None,
+        };
+
+        if self.reconnect.is_background() {
+            ConnectionEffect::None
+        } else {
+            ConnectionEffect::Connect(delay)
+        }
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine() -> ConnectionStateMachine {
+        ConnectionStateMachine::new(ReconnectPolicyConfig {
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_millis(1500),
+            jitter: 0.0,
+            max_fast_attempts: 3,
+            background_interval: Duration::from_secs(60),
+        })
+    }
+
+    #[test]
+    fn test_activation_connects() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::Activate, now),
+            ConnectionEffect::Connect(Duration::ZERO)
+        );
+        assert_eq!(machine.state(), &ConnectionState::Activating(1));
+
+        machine.handle(ConnectionEvent::ClientConnected, now);
+        assert_eq!(machine.state(), &ConnectionState::Connected);
+        assert_eq!(machine.reconnect_state(), ReconnectState::default());
+    }
+
+    #[test]
+    fn test_failed_attempts_back_off_then_continue_in_background() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.handle(ConnectionEvent::Activate, now);
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::ClientFailed, now),
+            ConnectionEffect::Connect(Duration::from_millis(200))
+        );
+        assert_eq!(machine.state(), &ConnectionState::Activating(2));
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::ClientFailed, now),
+            ConnectionEffect::Connect(Duration::from_millis(400))
+        );
+
+        // Fast attempts are exhausted, so the next attempt waits for a Tick
+        assert_eq!(machine.handle(ConnectionEvent::ClientFailed, now), ConnectionEffect::None);
+        assert_eq!(machine.state(), &ConnectionState::Activating(4));
+        assert!(machine.reconnect_state().is_background);
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::Tick, now + Duration::from_secs(30)),
+            ConnectionEffect::None
+        );
+        assert_eq!(
+            machine.handle(ConnectionEvent::Tick, now + Duration::from_secs(60)),
+            ConnectionEffect::Connect(Duration::ZERO)
+        );
+        assert_eq!(
+            machine.handle(ConnectionEvent::Tick, now + Duration::from_secs(61)),
+            ConnectionEffect::None
+        );
+    }
+
+    #[test]
+    fn test_lost_connection_reconnects_once() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.handle(ConnectionEvent::ClientConnected, now);
+
+        // A recoverable disconnect is followed by the client failing; only the failure results
+        // in a connection attempt.
+        let disconnected = ConnectionEvent::ClientDisconnected {
+            consider_reconnecting: true,
+        };
+        assert_eq!(machine.handle(disconnected, now), ConnectionEffect::None);
+        assert_eq!(machine.state(), &ConnectionState::Reconnecting(0));
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::ClientFailed, now),
+            ConnectionEffect::Connect(Duration::ZERO)
+        );
+        assert_eq!(machine.state(), &ConnectionState::Reconnecting(1));
+    }
+
+    #[test]
+    fn test_connection_test() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.handle(ConnectionEvent::ClientConnected, now);
+
+        assert_eq!(machine.handle(ConnectionEvent::TestRequested, now), ConnectionEffect::Test);
+        assert_eq!(machine.state(), &ConnectionState::Testing);
+
+        // A second test request while testing is ignored
+        assert_eq!(machine.handle(ConnectionEvent::TestRequested, now), ConnectionEffect::None);
+
+        machine.handle(ConnectionEvent::ClientConnected, now);
+        assert_eq!(machine.state(), &ConnectionState::Connected);
+
+        // A failed test results in a reconnect
+        machine.handle(ConnectionEvent::TestRequested, now);
+        machine.handle(
+            ConnectionEvent::ClientDisconnected {
+                consider_reconnecting: false,
+            },
+            now,
+        );
+        assert_eq!(machine.state(), &ConnectionState::Reconnecting(0));
+    }
+
+    #[test]
+    fn test_test_without_connection_reconnects() {
+        let mut machine = machine();
+
+        assert_eq!(
+            machine.handle(ConnectionEvent::TestRequested, Instant::now()),
+            ConnectionEffect::Connect(Duration::ZERO)
+        );
+        assert_eq!(machine.state(), &ConnectionState::Reconnecting(1));
+    }
+
+    #[test]
+    fn test_discovery_only_shown_while_resting() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.handle(ConnectionEvent::DiscoveryStarted, now);
+        assert_eq!(machine.state(), &ConnectionState::Discovering);
+        assert!(machine.is_discovering());
+
+        machine.handle(ConnectionEvent::Activate, now);
+        assert_eq!(machine.state(), &ConnectionState::Activating(1));
+
+        // Stopping while discovery is still underway returns to Discovering
+        machine.handle(ConnectionEvent::Stopped, now);
+        assert_eq!(machine.state(), &ConnectionState::Discovering);
+
+        machine.handle(ConnectionEvent::DiscoveryEnded, now);
+        assert_eq!(machine.state(), &ConnectionState::Idle);
+        assert!(!machine.is_discovering());
+    }
+
+    #[test]
+    fn test_no_connection_target_gives_up() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.handle(ConnectionEvent::Activate, now);
+        machine.handle(ConnectionEvent::NoConnectionTarget, now);
+        assert!(matches!(machine.state(), ConnectionState::GivingUp(_)));
+
+        // Client failures are ignored once we've given up
+        assert_eq!(machine.handle(ConnectionEvent::ClientFailed, now), ConnectionEffect::None);
+    }
+}
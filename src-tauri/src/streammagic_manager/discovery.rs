@@ -43,7 +43,7 @@ pub async fn discover_streamers(
     let mut seen_udns: HashSet<String> = HashSet::new();
     let mut found_count = 0;
 
-    send_app_log!(mgr, Info, "Performing streamer UPnP discovery (MediaRenderer only)");
+    send_app_log!(Info, "Performing streamer UPnP discovery (MediaRenderer only)");
 
     match rupnp::discover(&search_target, Duration::from_secs(3)).await {
         Ok(discovered_devices) => {
@@ -72,7 +72,7 @@ pub async fn discover_streamers(
                         is_active: false,
                     };
 
-                    send_app_log!(mgr, Info,
+                    send_app_log!(Info,
                         "StreamMagic device discovered: {} @ {}",
                         &streammagic_device, &streammagic_device.url
                     );
@@ -106,7 +106,7 @@ pub async fn discover_streamers(
                         }
                     }
                 } else {
-                    send_app_log!(mgr, Info,
+                    send_app_log!(Info,
                         "Streamer UPnP discovery is ignoring MediaRenderer device '{}' ({}) from {}",
                         device.friendly_name(),
                         device.model_name(),
@@ -116,12 +116,11 @@ pub async fn discover_streamers(
             }
         }
         Err(e) => {
-            send_app_log!(mgr, Error, "Streamer UPnP discovery error: {:?}", e);
+            send_app_log!(Error, "Streamer UPnP discovery error: {:?}", e);
         }
     }
 
     send_app_log!(
-        mgr,
         Info,
         "Streamer UPnP discovery found {} streamer{}",
         &found_count,
@@ -0,0 +1,149 @@
+//! Holds StreamerActions requested while there's no Streamer connection (e.g. during a reconnect),
+//! so they can be sent once the connection is re-established.
+//!
+//! The queue is bounded, and each action has a time-to-live. Actions which respond to what's
+//! happening right now (transport controls, volume changes, etc) are only worth performing if the
+//! connection comes back quickly, whereas actions like queue edits remain useful for longer.
+//! Actions which expire, or which are pushed out of a full queue, are returned to the caller so
+//! they can be reported to the UI.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::streammagic_manager::StreamerAction;
+
+// Time-to-live for actions which only make sense if performed promptly
+const SHORT_TTL: Duration = Duration::from_secs(5);
+// Time-to-live for all other actions
+const LONG_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub struct QueuedAction {
+    pub action: StreamerAction,
+    pub queued_at: Instant,
+    pub ttl: Duration,
+}
+
+impl QueuedAction {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.queued_at) > self.ttl
+    }
+}
+
+pub struct OutgoingQueue {
+    queue: VecDeque<QueuedAction>,
+    capacity: usize,
+}
+
+impl OutgoingQueue {
+    pub fn new(capacity: usize) -> Self {
+        OutgoingQueue {
+            queue: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Queue an action for sending later. If the queue is full then the oldest action is removed
+    /// to make room, and is returned.
+    pub fn push(&mut self, action: StreamerAction, now: Instant) -> Option<QueuedAction> {
+        let ttl = action_ttl(&action);
+
+        self.queue.push_back(QueuedAction {
+            action,
+            queued_at: now,
+            ttl,
+        });
+
+        if self.queue.len() > self.capacity {
+            self.queue.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Remove and return all actions which have outlived their time-to-live.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<QueuedAction> {
+        // Actions have different TTLs, so expired actions aren't necessarily at the front
+        let (expired, unexpired): (VecDeque<QueuedAction>, VecDeque<QueuedAction>) =
+            self.queue.drain(..).partition(|queued| queued.is_expired(now));
+        self.queue = unexpired;
+
+        expired.into()
+    }
+
+    /// Remove and return all queued actions, in the order they were queued.
+    pub fn take_all(&mut self) -> Vec<QueuedAction> {
+        self.queue.drain(..).collect()
+    }
+}
+
+fn action_ttl(action: &StreamerAction) -> Duration {
+    match action.zone_and_action().1 {
+        StreamerAction::MuteSet(_)
+        | StreamerAction::NextTrack
+        | StreamerAction::Pause
+        | StreamerAction::Play
+        | StreamerAction::PreviousTrack
+        | StreamerAction::Seek(_)
+        | StreamerAction::SeekPercent(_)
+        | StreamerAction::SeekRelative(_)
+        | StreamerAction::Stop
+        | StreamerAction::TogglePlayback
+        | StreamerAction::VolumePercentSet(_)
+        | StreamerAction::VolumeStepChange(_)
+        | StreamerAction::VolumeStepSet(_) => SHORT_TTL,
+        _ => LONG_TTL,
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_oldest_action_when_full() {
+        let mut queue = OutgoingQueue::new(2);
+        let now = Instant::now();
+
+        assert!(queue.push(StreamerAction::Play, now).is_none());
+        assert!(queue.push(StreamerAction::QueueClear, now).is_none());
+
+        let dropped = queue.push(StreamerAction::NextTrack, now).unwrap();
+        assert!(matches!(dropped.action, StreamerAction::Play));
+
+        let remaining: Vec<StreamerAction> = queue.take_all().into_iter().map(|queued| queued.action).collect();
+        assert!(matches!(remaining[..], [StreamerAction::QueueClear, StreamerAction::NextTrack]));
+        assert!(queue.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_expires_actions_by_ttl() {
+        let mut queue = OutgoingQueue::new(10);
+        let now = Instant::now();
+
+        queue.push(StreamerAction::QueueClear, now);
+        queue.push(StreamerAction::TogglePlayback, now);
+        queue.push(
+            StreamerAction::InZone {
+                zone: "ZONE2".to_string(),
+                action: Box::new(StreamerAction::VolumeStepChange(1)),
+            },
+            now,
+        );
+
+        assert!(queue.take_expired(now + Duration::from_secs(5)).is_empty());
+
+        let expired = queue.take_expired(now + Duration::from_secs(6));
+        assert_eq!(expired.len(), 2);
+        assert!(matches!(expired[0].action, StreamerAction::TogglePlayback));
+        assert!(matches!(expired[1].action, StreamerAction::InZone { .. }));
+
+        assert!(queue.take_expired(now + Duration::from_secs(30)).is_empty());
+        assert_eq!(queue.take_expired(now + Duration::from_secs(31)).len(), 1);
+        assert!(queue.take_all().is_empty());
+    }
+}
@@ -6,7 +6,8 @@ use ts_rs::TS;
 /// StreamMagic payloads.
 ///
 /// Capabilities:
-///   * Generate StreamMagic path update request messages (`RequestUpdates` trait).
+///   * Generate typed outgoing StreamMagic requests (`StreamMagicRequest`), including path update
+///     requests (`RequestUpdates` trait).
 ///   * Deserialize StreamMagic path update payloads.
 ///
 /// Note: Many fields are marked Optional to allow serde to deserialize as many fields as it finds.
@@ -18,14 +19,14 @@ use ts_rs::TS;
 ///  like "pub type StreamerZonePosition = WithZone<ZonePosition>;", but instead is a transparent
 ///  struct. See: https://github.com/Aleph-Alpha/ts-rs/issues/51#issuecomment-969133678
 
-#[derive(Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/WithZone.ts")]
 pub struct WithZone<T> {
     pub zone: String,
     pub data: T,
 }
 
-#[derive(Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/WithoutZone.ts")]
 pub struct WithoutZone<T> {
     data: T,
@@ -48,25 +49,165 @@ pub struct StreamMagicMessage {
 }
 
 pub trait RequestUpdates {
-    fn request_updates_msg() -> String;
+    fn request_updates_msg() -> StreamMagicRequest;
+}
+
+/// Payloads which describe a single zone.
+pub trait ZonePayload {
+    fn zone(&self) -> &str;
 }
 
 // ================================================================================================
-// Outgoing streamer control payloads
+// Outgoing streamer requests
 // ================================================================================================
 
+/// An outgoing StreamMagic request, one variant per StreamMagic path.
+///
+/// Serializes to the `{"path": "<path>", "params": {...}}` shape expected by the streamer. Params
+/// structs only serialize the fields which have been set, so a single params struct can be used for
+/// all the different requests which can be made of a given path.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "path", content = "params")]
+pub enum StreamMagicRequest {
+    #[serde(rename = "/presets/delete")]
+    PresetsDelete(PresetSlotParams),
+    #[serde(rename = "/presets/list")]
+    PresetsList(UpdateParams),
+    #[serde(rename = "/presets/rename")]
+    PresetsRename(PresetRenameParams),
+    #[serde(rename = "/presets/store")]
+    PresetsStore(PresetSlotParams),
+    #[serde(rename = "/queue/add")]
+    QueueAdd(QueueAddParams),
+    #[serde(rename = "/queue/clear")]
+    QueueClear(QueueClearParams),
+    #[serde(rename = "/queue/delete")]
+    QueueDelete(QueueDeleteParams),
+    #[serde(rename = "/queue/info")]
+    QueueInfo(UpdateParams),
+    #[serde(rename = "/queue/list")]
+    QueueList(QueueListParams),
+    #[serde(rename = "/queue/move")]
+    QueueMove(QueueMoveParams),
+    #[serde(rename = "/stream/radio")]
+    StreamRadio(StreamRadioParams),
+    #[serde(rename = "/system/display")]
+    SystemDisplay(SystemDisplayParams),
+    #[serde(rename = "/system/info")]
+    SystemInfo(UpdateParams),
+    #[serde(rename = "/system/power")]
+    SystemPower(SystemPowerParams),
+    #[serde(rename = "/system/sources")]
+    SystemSources(SystemSourcesParams),
+    #[serde(rename = "/system/update")]
+    SystemUpdate(SystemUpdateParams),
+    #[serde(rename = "/zone/audio")]
+    ZoneAudio(ZoneAudioParams),
+    #[serde(rename = "/zone/now_playing")]
+    ZoneNowPlaying(UpdateParams),
+    #[serde(rename = "/zone/play_control")]
+    ZonePlayControl(PlayControlParams),
+    #[serde(rename = "/zone/play_state")]
+    ZonePlayState(UpdateParams),
+    #[serde(rename = "/zone/play_state/position")]
+    ZonePosition(UpdateParams),
+    #[serde(rename = "/zone/recall_preset")]
+    ZoneRecallPreset(RecallPresetParams),
+    #[serde(rename = "/zone/state")]
+    ZoneState(ZoneStateParams),
+}
+
+impl StreamMagicRequest {
+    /// The StreamMagic path this request will be sent to.
+    pub fn path(&self) -> &'static str {
+        match self {
+            StreamMagicRequest::PresetsDelete(_) => "/presets/delete",
+            StreamMagicRequest::PresetsList(_) => "/presets/list",
+            StreamMagicRequest::PresetsRename(_) => "/presets/rename",
+            StreamMagicRequest::PresetsStore(_) => "/presets/store",
+            StreamMagicRequest::QueueAdd(_) => "/queue/add",
+            StreamMagicRequest::QueueClear(_) => "/queue/clear",
+            StreamMagicRequest::QueueDelete(_) => "/queue/delete",
+            StreamMagicRequest::QueueInfo(_) => "/queue/info",
+            StreamMagicRequest::QueueList(_) => "/queue/list",
+            StreamMagicRequest::QueueMove(_) => "/queue/move",
+            StreamMagicRequest::StreamRadio(_) => "/stream/radio",
+            StreamMagicRequest::SystemDisplay(_) => "/system/display",
+            StreamMagicRequest::SystemInfo(_) => "/system/info",
+            StreamMagicRequest::SystemPower(_) => "/system/power",
+            StreamMagicRequest::SystemSources(_) => "/system/sources",
+            StreamMagicRequest::SystemUpdate(_) => "/system/update",
+            StreamMagicRequest::ZoneAudio(_) => "/zone/audio",
+            StreamMagicRequest::ZoneNowPlaying(_) => "/zone/now_playing",
+            StreamMagicRequest::ZonePlayControl(_) => "/zone/play_control",
+            StreamMagicRequest::ZonePlayState(_) => "/zone/play_state",
+            StreamMagicRequest::ZonePosition(_) => "/zone/play_state/position",
+            StreamMagicRequest::ZoneRecallPreset(_) => "/zone/recall_preset",
+            StreamMagicRequest::ZoneState(_) => "/zone/state",
+        }
+    }
+
+    /// Target the request at the given zone. Requests for paths which are not zone-scoped are
+    /// returned unchanged.
+    pub fn with_zone(self, zone: String) -> Self {
+        let zone = Some(zone);
+
+        match self {
+            StreamMagicRequest::StreamRadio(params) => {
+                StreamMagicRequest::StreamRadio(StreamRadioParams { zone, ..params })
+            }
+            StreamMagicRequest::ZoneAudio(params) => StreamMagicRequest::ZoneAudio(ZoneAudioParams { zone, ..params }),
+            StreamMagicRequest::ZoneNowPlaying(params) => {
+                StreamMagicRequest::ZoneNowPlaying(UpdateParams { zone, ..params })
+            }
+            StreamMagicRequest::ZonePlayControl(params) => {
+                StreamMagicRequest::ZonePlayControl(PlayControlParams { zone, ..params })
+            }
+            StreamMagicRequest::ZonePlayState(params) => {
+                StreamMagicRequest::ZonePlayState(UpdateParams { zone, ..params })
+            }
+            StreamMagicRequest::ZonePosition(params) => {
+                StreamMagicRequest::ZonePosition(UpdateParams { zone, ..params })
+            }
+            StreamMagicRequest::ZoneRecallPreset(params) => {
+                StreamMagicRequest::ZoneRecallPreset(RecallPresetParams { zone, ..params })
+            }
+            StreamMagicRequest::ZoneState(params) => StreamMagicRequest::ZoneState(ZoneStateParams { zone, ..params }),
+            request => request,
+        }
+    }
+}
+
+/// Params for paths which only support requesting their state (and updates to it).
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UpdateParams {
+    // Request the current state only (rather than subscribing to updates) when not provided
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+    // Only applicable to /zone/* paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+impl UpdateParams {
+    pub fn enabled() -> Self {
+        UpdateParams { update: Some(1), zone: None }
+    }
+
+    pub fn current_state() -> Self {
+        UpdateParams { update: None, zone: None }
+    }
+}
+
 // PlayControl ------------------------------------------------------------------------------------
 
-// PlayControl is only used to send commands to the streamer; we don't send this information to
+// PlayControl is only used to build requests for the streamer; we don't send this information to
 // the UI, so no need to support serialization.
 pub struct PlayControl {}
 
-#[derive(Clone, Debug, Serialize, TS)]
-#[ts(
-    export,
-    rename_all = "lowercase",
-    export_to = "../src/types/generated/streammagic_payloads/TransportToggleState.ts"
-)]
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/TransportToggleState.ts")]
 pub enum TransportToggleState {
     All,
     Off,
@@ -114,78 +255,274 @@ impl<'de> Visitor<'de> for TransportToggleStateVisitor {
     }
 }
 
-/// Generate StreamMagic payloads for streamer PlayControl actions.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayControlAction {
+    Pause,
+    Play,
+    Stop,
+    Toggle,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct PlayControlParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<PlayControlAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_track: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_repeat: Option<TransportToggleState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode_shuffle: Option<TransportToggleState>,
+    // The zone to target. The streamer uses its default zone when this is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+/// Generate StreamMagic requests for streamer PlayControl actions.
 impl PlayControl {
-    pub fn next_track_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"skip_track": 1}}"#)
+    fn request(params: PlayControlParams) -> StreamMagicRequest {
+        StreamMagicRequest::ZonePlayControl(params)
     }
 
-    pub fn pause_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"action": "pause"}}"#)
+    fn action_msg(action: PlayControlAction) -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            action: Some(action),
+            ..Default::default()
+        })
     }
 
-    pub fn play_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"action": "play"}}"#)
+    pub fn next_track_msg() -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            skip_track: Some(1),
+            ..Default::default()
+        })
     }
 
-    pub fn play_queue_id_msg(queue_id: i32) -> String {
-        String::from(format!(
-            r#"
-            {{"path": "/zone/play_control", "params": {{"queue_id": {queue_id}}}}}
-        "#
-        ))
+    pub fn pause_msg() -> StreamMagicRequest {
+        PlayControl::action_msg(PlayControlAction::Pause)
     }
 
-    pub fn previous_track_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"skip_track": -1}}"#)
+    pub fn play_msg() -> StreamMagicRequest {
+        PlayControl::action_msg(PlayControlAction::Play)
     }
 
-    pub fn seek_msg(position: i32) -> String {
-        String::from(format!(
-            r#"
-            {{"path": "/zone/play_control", "params": {{"position": {position}}}}}
-        "#
-        ))
+    pub fn play_queue_id_msg(queue_id: i32) -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            queue_id: Some(queue_id),
+            ..Default::default()
+        })
     }
 
-    pub fn set_repeat_msg(state: TransportToggleState) -> String {
-        String::from(format!(
-            r#"
-            {{"path": "/zone/play_control", "params": {{"mode_repeat": "{state}"}}}}
-        "#
-        ))
+    pub fn previous_track_msg() -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            skip_track: Some(-1),
+            ..Default::default()
+        })
     }
 
-    pub fn set_shuffle_msg(state: TransportToggleState) -> String {
-        String::from(format!(
-            r#"
-            {{"path": "/zone/play_control", "params": {{"mode_shuffle": "{state}"}}}}
-        "#
-        ))
+    pub fn seek_msg(position: i32) -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            position: Some(position),
+            ..Default::default()
+        })
     }
 
-    pub fn stop_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"action": "stop"}}"#)
+    /// Seek to a percentage (0-100) of the track's duration (in seconds).
+    pub fn seek_percent_msg(percent: u8, duration: i32) -> StreamMagicRequest {
+        PlayControl::seek_msg(duration * percent.min(100) as i32 / 100)
     }
 
-    pub fn toggle_playback_msg() -> String {
-        String::from(r#"{"path": "/zone/play_control", "params": {"action": "toggle"}}"#)
+    /// Seek by an offset (in seconds) from the current position, clamped to the track's duration.
+    pub fn seek_relative_msg(position: i32, offset: i32, duration: i32) -> StreamMagicRequest {
+        PlayControl::seek_msg(position.saturating_add(offset).clamp(0, duration))
+    }
+
+    pub fn set_repeat_msg(state: TransportToggleState) -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            mode_repeat: Some(state),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_shuffle_msg(state: TransportToggleState) -> StreamMagicRequest {
+        PlayControl::request(PlayControlParams {
+            mode_shuffle: Some(state),
+            ..Default::default()
+        })
+    }
+
+    pub fn stop_msg() -> StreamMagicRequest {
+        PlayControl::action_msg(PlayControlAction::Stop)
+    }
+
+    pub fn toggle_playback_msg() -> StreamMagicRequest {
+        PlayControl::action_msg(PlayControlAction::Toggle)
+    }
+}
+
+// QueueControl -----------------------------------------------------------------------------------
+
+// QueueControl is only used to build requests for the streamer; we don't send this information to
+// the UI, so no need to support serialization.
+pub struct QueueControl {}
+
+/// Where in the queue to add a new item.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/QueueAddPosition.ts")]
+pub enum QueueAddPosition {
+    // Immediately after the currently-playing item
+    Next,
+    // After the last item in the queue
+    End,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct QueueAddParams {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub position: QueueAddPosition,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct QueueClearParams {}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct QueueDeleteParams {
+    pub ids: Vec<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct QueueMoveParams {
+    pub id: i32,
+    pub position: i32,
+}
+
+/// Generate StreamMagic requests for editing the streamer's queue.
+impl QueueControl {
+    pub fn add_msg(url: String, title: Option<String>, position: QueueAddPosition) -> StreamMagicRequest {
+        StreamMagicRequest::QueueAdd(QueueAddParams { url, title, position })
+    }
+
+    pub fn clear_msg() -> StreamMagicRequest {
+        StreamMagicRequest::QueueClear(Default::default())
+    }
+
+    pub fn move_id_msg(queue_id: i32, position: i32) -> StreamMagicRequest {
+        StreamMagicRequest::QueueMove(QueueMoveParams {
+            id: queue_id,
+            position,
+        })
+    }
+
+    pub fn remove_ids_msg(queue_ids: Vec<i32>) -> StreamMagicRequest {
+        StreamMagicRequest::QueueDelete(QueueDeleteParams { ids: queue_ids })
     }
 }
 
 // RecallPreset -----------------------------------------------------------------------------------
 
-// RecallPreset is only used to send commands to the streamer; we don't send this information to
+// RecallPreset is only used to build requests for the streamer; we don't send this information to
 // the UI, so no need to support serialization.
 pub struct RecallPreset {}
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecallPresetParams {
+    pub preset: i32,
+    // The zone to target. The streamer uses its default zone when this is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
 impl RecallPreset {
-    pub fn play_preset_id_msg(preset_id: i32) -> String {
-        String::from(format!(
-            r#"
-            {{"path": "/zone/recall_preset", "params": {{"preset": {preset_id}}}}}
-        "#
-        ))
+    pub fn play_preset_id_msg(preset_id: i32) -> StreamMagicRequest {
+        StreamMagicRequest::ZoneRecallPreset(RecallPresetParams {
+            preset: preset_id,
+            zone: None,
+        })
+    }
+}
+
+// PresetControl ----------------------------------------------------------------------------------
+
+// PresetControl is only used to build requests for the streamer; we don't send this information to
+// the UI, so no need to support serialization. The resulting preset changes are received via
+// /presets/list updates.
+pub struct PresetControl {}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PresetSlotParams {
+    pub preset: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PresetRenameParams {
+    pub preset: i32,
+    pub name: String,
+}
+
+/// Generate StreamMagic requests for managing the streamer's presets.
+impl PresetControl {
+    pub fn clear_preset_id_msg(preset_id: i32) -> StreamMagicRequest {
+        StreamMagicRequest::PresetsDelete(PresetSlotParams { preset: preset_id })
+    }
+
+    pub fn rename_preset_id_msg(preset_id: i32, name: String) -> StreamMagicRequest {
+        StreamMagicRequest::PresetsRename(PresetRenameParams {
+            preset: preset_id,
+            name,
+        })
+    }
+
+    /// Store the currently-playing stream in the given preset slot.
+    pub fn store_preset_id_msg(preset_id: i32) -> StreamMagicRequest {
+        StreamMagicRequest::PresetsStore(PresetSlotParams { preset: preset_id })
+    }
+}
+
+// StreamRadio ------------------------------------------------------------------------------------
+
+// StreamRadio is only used to build requests for the streamer; we don't send this information to
+// the UI, so no need to support serialization.
+pub struct StreamRadio {}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct StreamRadioParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub airable_radio_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub art_url: Option<String>,
+    // The zone to target. The streamer uses its default zone when this is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+/// Generate StreamMagic requests for playing radio stations which aren't stored as presets.
+impl StreamRadio {
+    pub fn play_airable_radio_id_msg(airable_radio_id: i64) -> StreamMagicRequest {
+        StreamMagicRequest::StreamRadio(StreamRadioParams {
+            airable_radio_id: Some(airable_radio_id),
+            ..Default::default()
+        })
+    }
+
+    pub fn play_url_msg(url: String, name: String, art_url: Option<String>) -> StreamMagicRequest {
+        StreamMagicRequest::StreamRadio(StreamRadioParams {
+            url: Some(url),
+            name: Some(name),
+            art_url,
+            ..Default::default()
+        })
     }
 }
 
@@ -195,7 +532,7 @@ impl RecallPreset {
 
 // QueueList --------------------------------------------------------------------------------------
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Serialize, Deserialize, TS)]
 #[ts(
     export,
     export_to = "../src/types/generated/streammagic_payloads/QueueListItemMetadata.ts"
@@ -213,7 +550,7 @@ pub struct QueueListItemMetadata {
     pub artist: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/QueueListItem.ts")]
 pub struct QueueListItem {
     pub id: Option<i32>,
@@ -221,7 +558,7 @@ pub struct QueueListItem {
     pub metadata: Option<QueueListItemMetadata>,
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/QueueList.ts")]
 pub struct QueueList {
     pub start: Option<i32>,
@@ -232,10 +569,12 @@ pub struct QueueList {
     pub items: Option<Vec<QueueListItem>>,
 }
 
-impl QueueList {
-    pub fn request_state_msg() -> String {
-        String::from(r#"{"path": "/queue/list"}"#)
-    }
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct QueueListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -245,6 +584,16 @@ impl QueueList {
 )]
 pub struct StreamerQueueList(WithoutZone<QueueList>);
 
+impl StreamerQueueList {
+    pub fn new(queue_list: QueueList) -> Self {
+        StreamerQueueList(WithoutZone { data: queue_list })
+    }
+
+    pub fn into_queue_list(self) -> QueueList {
+        self.0.data
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 
 #[derive(Serialize, Deserialize)]
@@ -256,15 +605,27 @@ pub struct QueueInfo {
     pub play_id: Option<i64>,
 }
 
+impl QueueInfo {
+    pub fn request_state_msg() -> StreamMagicRequest {
+        StreamMagicRequest::QueueInfo(UpdateParams::current_state())
+    }
+}
+
 impl RequestUpdates for QueueInfo {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/queue/info", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::QueueInfo(UpdateParams::enabled())
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct StreamerQueueInfo(WithoutZone<QueueInfo>);
 
+impl StreamerQueueInfo {
+    pub fn queue_info(&self) -> &QueueInfo {
+        &self.0.data
+    }
+}
+
 // Presets ----------------------------------------------------------------------------------------
 
 #[derive(Serialize, Deserialize, TS)]
@@ -292,8 +653,8 @@ pub struct Presets {
 }
 
 impl RequestUpdates for Presets {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/presets/list", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::PresetsList(UpdateParams::enabled())
     }
 }
 
@@ -329,8 +690,8 @@ pub struct SystemInfo {
 }
 
 impl RequestUpdates for SystemInfo {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/system/info", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::SystemInfo(UpdateParams::enabled())
     }
 }
 
@@ -340,36 +701,116 @@ pub struct StreamerSystemInfo(WithoutZone<SystemInfo>);
 
 // SystemPower ------------------------------------------------------------------------------------
 
-#[derive(Debug, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/SystemPower.ts")]
 pub struct SystemPower {
     pub power: String,
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum SystemPowerTarget {
+    #[serde(rename = "ON")]
+    On,
+    #[serde(rename = "NETWORK")]
+    Network,
+    #[serde(rename = "toggle")]
+    Toggle,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SystemPowerParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<SystemPowerTarget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+}
+
 impl SystemPower {
-    pub fn on_msg() -> String {
-        String::from(r#"{"path": "/system/power", "params": {"power": "ON"}}"#)
+    fn power_msg(target: SystemPowerTarget) -> StreamMagicRequest {
+        StreamMagicRequest::SystemPower(SystemPowerParams {
+            power: Some(target),
+            ..Default::default()
+        })
+    }
+
+    pub fn on_msg() -> StreamMagicRequest {
+        SystemPower::power_msg(SystemPowerTarget::On)
     }
 
-    pub fn standby_msg() -> String {
-        String::from(r#"{"path": "/system/power", "params": {"power": "NETWORK"}}"#)
+    pub fn standby_msg() -> StreamMagicRequest {
+        SystemPower::power_msg(SystemPowerTarget::Network)
     }
 
-    pub fn toggle_msg() -> String {
-        String::from(r#"{"path": "/system/power", "params": {"power": "toggle"}}"#)
+    pub fn toggle_msg() -> StreamMagicRequest {
+        SystemPower::power_msg(SystemPowerTarget::Toggle)
     }
 }
 
 impl RequestUpdates for SystemPower {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/system/power", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::SystemPower(SystemPowerParams {
+            update: Some(1),
+            ..Default::default()
+        })
     }
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/StreamerSystemPower.ts")]
 pub struct StreamerSystemPower(WithoutZone<SystemPower>);
 
+// SystemDisplay ----------------------------------------------------------------------------------
+
+/// Front-panel display brightness.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/DisplayBrightness.ts")]
+pub enum DisplayBrightness {
+    Bright,
+    Dim,
+    Off,
+    // Any other brightness reported by the streamer, so it doesn't prevent the rest of the
+    // SystemDisplay payload from being decoded. This can't be set.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/SystemDisplay.ts")]
+pub struct SystemDisplay {
+    pub brightness: Option<DisplayBrightness>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SystemDisplayParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<DisplayBrightness>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+}
+
+impl SystemDisplay {
+    pub fn set_brightness_msg(brightness: DisplayBrightness) -> StreamMagicRequest {
+        StreamMagicRequest::SystemDisplay(SystemDisplayParams {
+            brightness: Some(brightness),
+            ..Default::default()
+        })
+    }
+}
+
+impl RequestUpdates for SystemDisplay {
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::SystemDisplay(SystemDisplayParams {
+            update: Some(1),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/StreamerSystemDisplay.ts")]
+pub struct StreamerSystemDisplay(WithoutZone<SystemDisplay>);
+
 // SystemSources ----------------------------------------------------------------------------------
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -392,9 +833,76 @@ pub struct SystemSources {
     pub sources: Vec<Source>,
 }
 
+/// Changes to the settings of a single source. Only the fields which have been set are sent.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SourceSettingsParams {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui_selectable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_order: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SystemSourcesParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<SourceSettingsParams>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+}
+
+impl SystemSources {
+    fn sources_msg(sources: Vec<SourceSettingsParams>) -> StreamMagicRequest {
+        StreamMagicRequest::SystemSources(SystemSourcesParams {
+            sources: Some(sources),
+            ..Default::default()
+        })
+    }
+
+    pub fn rename_source_msg(source_id: String, name: String) -> StreamMagicRequest {
+        SystemSources::sources_msg(vec![SourceSettingsParams {
+            id: source_id,
+            name: Some(name),
+            ..Default::default()
+        }])
+    }
+
+    /// Reorder the sources, where the position of each source id is its new preferred order.
+    pub fn reorder_sources_msg(source_ids: Vec<String>) -> StreamMagicRequest {
+        SystemSources::sources_msg(
+            source_ids
+                .into_iter()
+                .enumerate()
+                .map(|(order, id)| SourceSettingsParams {
+                    id,
+                    preferred_order: Some(order as i64),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn set_source_visible_msg(source_id: String, is_visible: bool) -> StreamMagicRequest {
+        SystemSources::sources_msg(vec![SourceSettingsParams {
+            id: source_id,
+            ui_selectable: Some(is_visible),
+            ..Default::default()
+        }])
+    }
+
+    pub fn source(&self, source_id: &str) -> Option<&Source> {
+        self.sources.iter().find(|source| source.id == source_id)
+    }
+}
+
 impl RequestUpdates for SystemSources {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/system/sources", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::SystemSources(SystemSourcesParams {
+            update: Some(1),
+            ..Default::default()
+        })
     }
 }
 
@@ -405,6 +913,66 @@ impl RequestUpdates for SystemSources {
 )]
 pub struct StreamerSystemSources(WithoutZone<SystemSources>);
 
+impl StreamerSystemSources {
+    pub fn system_sources(&self) -> &SystemSources {
+        &self.0.data
+    }
+}
+
+// SystemUpdate -----------------------------------------------------------------------------------
+
+/// Firmware update status.
+#[derive(Debug, Deserialize, Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/SystemUpdate.ts")]
+pub struct SystemUpdate {
+    pub update_available: Option<bool>,
+    pub updating: Option<bool>,
+    pub state: Option<String>,
+    pub progress: Option<u8>,
+    pub version: Option<String>,
+}
+
+/// What to ask of the streamer's firmware updater.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/SystemUpdateAction.ts")]
+pub enum SystemUpdateAction {
+    // Check whether an update is available
+    Check,
+    // Start installing an available update
+    Start,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SystemUpdateParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<SystemUpdateAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+}
+
+impl SystemUpdate {
+    pub fn action_msg(action: SystemUpdateAction) -> StreamMagicRequest {
+        StreamMagicRequest::SystemUpdate(SystemUpdateParams {
+            action: Some(action),
+            ..Default::default()
+        })
+    }
+}
+
+impl RequestUpdates for SystemUpdate {
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::SystemUpdate(SystemUpdateParams {
+            update: Some(1),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/StreamerSystemUpdate.ts")]
+pub struct StreamerSystemUpdate(WithoutZone<SystemUpdate>);
+
 // ZoneNowPlaying ---------------------------------------------------------------------------------
 
 #[derive(Serialize, Deserialize, TS)]
@@ -469,8 +1037,8 @@ pub struct ZoneNowPlaying {
 }
 
 impl RequestUpdates for ZoneNowPlaying {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/zone/now_playing", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::ZoneNowPlaying(UpdateParams::enabled())
     }
 }
 
@@ -481,9 +1049,21 @@ impl RequestUpdates for ZoneNowPlaying {
 )]
 pub struct StreamerZoneNowPlaying(WithZone<ZoneNowPlaying>);
 
+impl StreamerZoneNowPlaying {
+    pub fn now_playing(&self) -> &ZoneNowPlaying {
+        &self.0.data
+    }
+}
+
+impl ZonePayload for StreamerZoneNowPlaying {
+    fn zone(&self) -> &str {
+        &self.0.zone
+    }
+}
+
 // ZonePlayState ----------------------------------------------------------------------------------
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(
     export,
     export_to = "../src/types/generated/streammagic_payloads/ZonePlayStateMetadata.ts"
@@ -511,7 +1091,7 @@ pub struct ZonePlayStateMetadata {
     pub radio_id: Option<i64>, // These can be big numbers so bumping to i64
 }
 
-#[derive(Debug, Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/ZonePlayStateState.ts")]
 pub enum ZonePlayStateState {
@@ -525,7 +1105,7 @@ pub enum ZonePlayStateState {
     Stop,
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/ZonePlayState.ts")]
 pub struct ZonePlayState {
     pub state: Option<ZonePlayStateState>,
@@ -540,18 +1120,30 @@ pub struct ZonePlayState {
 }
 
 impl RequestUpdates for ZonePlayState {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/zone/play_state", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::ZonePlayState(UpdateParams::enabled())
     }
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(
     export,
     export_to = "../src/types/generated/streammagic_payloads/StreamerZonePlayState.ts"
 )]
 pub struct StreamerZonePlayState(WithZone<ZonePlayState>);
 
+impl ZonePayload for StreamerZonePlayState {
+    fn zone(&self) -> &str {
+        &self.0.zone
+    }
+}
+
+impl StreamerZonePlayState {
+    pub fn play_state(&self) -> &ZonePlayState {
+        &self.0.data
+    }
+}
+
 // ZonePosition -----------------------------------------------------------------------------------
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -561,8 +1153,8 @@ pub struct ZonePosition {
 }
 
 impl RequestUpdates for ZonePosition {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/zone/play_state/position", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        StreamMagicRequest::ZonePosition(UpdateParams::enabled())
     }
 }
 
@@ -573,9 +1165,21 @@ impl RequestUpdates for ZonePosition {
 )]
 pub struct StreamerZonePosition(WithZone<ZonePosition>);
 
+impl StreamerZonePosition {
+    pub fn position(&self) -> &ZonePosition {
+        &self.0.data
+    }
+}
+
+impl ZonePayload for StreamerZonePosition {
+    fn zone(&self) -> &str {
+        &self.0.zone
+    }
+}
+
 // ZoneState --------------------------------------------------------------------------------------
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../src/types/generated/streammagic_payloads/ZoneState.ts")]
 pub struct ZoneState {
     pub source: Option<String>,
@@ -589,47 +1193,658 @@ pub struct ZoneState {
     pub cbus: Option<String>,
 }
 
+/// Control Bus mode, for controlling a connected Cambridge Audio amplifier or receiver.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/ControlBusMode.ts")]
+pub enum ControlBusMode {
+    Off,
+    Amplifier,
+    Receiver,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ZoneStateParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_amp_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cbus: Option<ControlBusMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_step: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_step_change: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+    // The zone to target. The streamer uses its default zone when this is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
 impl ZoneState {
-    pub fn set_mute_msg(is_muted: bool) -> String {
-        String::from(format!(
-            r#"{{"path": "/zone/state", "params": {{"mute": {is_muted}}}}}"#
-        ))
+    fn request(params: ZoneStateParams) -> StreamMagicRequest {
+        StreamMagicRequest::ZoneState(params)
     }
 
-    pub fn set_source_id_msg(source_id: String) -> String {
-        String::from(format!(
-            r#"{{"path": "/zone/state", "params": {{"source": "{source_id}"}}}}"#
-        ))
+    pub fn request_state_msg() -> StreamMagicRequest {
+        ZoneState::request(Default::default())
     }
 
-    pub fn set_volume_percent_msg(percent: u8) -> String {
-        String::from(format!(
-            r#"{{"path": "/zone/state", "params": {{"volume_percent": {percent}}}}}"#
-        ))
+    pub fn set_control_bus_mode_msg(mode: ControlBusMode) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            cbus: Some(mode),
+            ..Default::default()
+        })
     }
 
-    pub fn change_volume_step_msg(degree: i8) -> String {
-        String::from(format!(
-            r#"{{"path": "/zone/state", "params": {{"volume_step_change": {degree}}}}}"#
-        ))
+    pub fn set_mute_msg(is_muted: bool) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            mute: Some(is_muted),
+            ..Default::default()
+        })
     }
 
-    pub fn set_volume_step_msg(step: u8) -> String {
-        String::from(format!(
-            r#"{{"path": "/zone/state", "params": {{"volume_step": {step}}}}}"#
-        ))
+    pub fn set_pre_amp_mode_msg(is_enabled: bool) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            pre_amp_mode: Some(is_enabled),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_source_id_msg(source_id: String) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            source: Some(source_id),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_volume_percent_msg(percent: u8) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            volume_percent: Some(percent),
+            ..Default::default()
+        })
+    }
+
+    pub fn change_volume_step_msg(degree: i8) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            volume_step_change: Some(degree),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_volume_step_msg(step: u8) -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            volume_step: Some(step),
+            ..Default::default()
+        })
     }
 }
 
 impl RequestUpdates for ZoneState {
-    fn request_updates_msg() -> String {
-        String::from(r#"{"path": "/zone/state", "params": {"update": 1}}"#)
+    fn request_updates_msg() -> StreamMagicRequest {
+        ZoneState::request(ZoneStateParams {
+            update: Some(1),
+            ..Default::default()
+        })
     }
 }
 
-#[derive(Serialize, Deserialize, TS)]
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(
     export,
     export_to = "../src/types/generated/streammagic_payloads/StreamerZoneState.ts"
 )]
 pub struct StreamerZoneState(WithZone<ZoneState>);
+
+impl ZonePayload for StreamerZoneState {
+    fn zone(&self) -> &str {
+        &self.0.zone
+    }
+}
+
+// ZoneAudio --------------------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/ZoneAudioEqBand.ts")]
+pub struct ZoneAudioEqBand {
+    pub index: Option<u8>,
+    pub filter: Option<String>,
+    pub freq: Option<i32>,
+    pub gain: Option<f32>,
+    pub q: Option<f32>,
+}
+
+/// Audio processing settings. Which fields are present depends on the streamer model.
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/ZoneAudio.ts")]
+pub struct ZoneAudio {
+    pub balance: Option<i8>,
+    pub tone_bass: Option<i8>,
+    pub tone_treble: Option<i8>,
+    pub user_eq: Option<bool>,
+    pub user_eq_bands: Option<Vec<ZoneAudioEqBand>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ZoneAudioEqBandParams {
+    pub index: u8,
+    pub gain: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ZoneAudioParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tone_bass: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tone_treble: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_eq: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_eq_bands: Option<Vec<ZoneAudioEqBandParams>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<u8>,
+    // The zone to target. The streamer uses its default zone when this is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+}
+
+impl ZoneAudio {
+    fn request(params: ZoneAudioParams) -> StreamMagicRequest {
+        StreamMagicRequest::ZoneAudio(params)
+    }
+
+    pub fn set_balance_msg(balance: i8) -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            balance: Some(balance),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_tone_bass_msg(bass: i8) -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            tone_bass: Some(bass),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_tone_treble_msg(treble: i8) -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            tone_treble: Some(treble),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_user_eq_enabled_msg(is_enabled: bool) -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            user_eq: Some(is_enabled),
+            ..Default::default()
+        })
+    }
+
+    pub fn set_user_eq_band_gain_msg(index: u8, gain: f32) -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            user_eq_bands: Some(vec![ZoneAudioEqBandParams { index, gain }]),
+            ..Default::default()
+        })
+    }
+}
+
+impl RequestUpdates for ZoneAudio {
+    fn request_updates_msg() -> StreamMagicRequest {
+        ZoneAudio::request(ZoneAudioParams {
+            update: Some(1),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../src/types/generated/streammagic_payloads/StreamerZoneAudio.ts")]
+pub struct StreamerZoneAudio(WithZone<ZoneAudio>);
+
+impl ZonePayload for StreamerZoneAudio {
+    fn zone(&self) -> &str {
+        &self.0.zone
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Check that the request serializes to the expected JSON, and deserializes back into an
+    /// identical request.
+    fn assert_round_trip(request: StreamMagicRequest, expected: serde_json::Value) {
+        let serialized = serde_json::to_string(&request).expect("Could not serialize request");
+        let serialized_value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(serialized_value, expected);
+
+        let deserialized: StreamMagicRequest =
+            serde_json::from_str(&serialized).expect("Could not deserialize request");
+        assert_eq!(deserialized, request);
+    }
+
+    // Update requests
+
+    #[test]
+    fn test_request_updates_msgs() {
+        let requests = [
+            (Presets::request_updates_msg(), "/presets/list"),
+            (QueueInfo::request_updates_msg(), "/queue/info"),
+            (SystemDisplay::request_updates_msg(), "/system/display"),
+            (SystemInfo::request_updates_msg(), "/system/info"),
+            (SystemPower::request_updates_msg(), "/system/power"),
+            (SystemSources::request_updates_msg(), "/system/sources"),
+            (SystemUpdate::request_updates_msg(), "/system/update"),
+            (ZoneAudio::request_updates_msg(), "/zone/audio"),
+            (ZoneNowPlaying::request_updates_msg(), "/zone/now_playing"),
+            (ZonePlayState::request_updates_msg(), "/zone/play_state"),
+            (ZonePosition::request_updates_msg(), "/zone/play_state/position"),
+            (ZoneState::request_updates_msg(), "/zone/state"),
+        ];
+
+        for (request, path) in requests {
+            assert_eq!(request.path(), path);
+            assert_round_trip(request, json!({"path": path, "params": {"update": 1}}));
+        }
+    }
+
+    #[test]
+    fn test_with_zone() {
+        assert_round_trip(
+            PlayControl::play_msg().with_zone("ZONE2".to_string()),
+            json!({"path": "/zone/play_control", "params": {"action": "play", "zone": "ZONE2"}}),
+        );
+        assert_round_trip(
+            ZoneState::set_mute_msg(true).with_zone("ZONE2".to_string()),
+            json!({"path": "/zone/state", "params": {"mute": true, "zone": "ZONE2"}}),
+        );
+        assert_round_trip(
+            ZonePosition::request_updates_msg().with_zone("ZONE2".to_string()),
+            json!({"path": "/zone/play_state/position", "params": {"update": 1, "zone": "ZONE2"}}),
+        );
+
+        // Requests for paths which aren't zone-scoped are unchanged
+        assert_eq!(SystemPower::on_msg().with_zone("ZONE2".to_string()), SystemPower::on_msg());
+    }
+
+    #[test]
+    fn test_queue_list_page_request() {
+        assert_round_trip(
+            StreamMagicRequest::QueueList(QueueListParams {
+                start: Some(100),
+                count: Some(50),
+            }),
+            json!({"path": "/queue/list", "params": {"start": 100, "count": 50}}),
+        );
+    }
+
+    // PlayControl
+
+    #[test]
+    fn test_play_control_action_msgs() {
+        let requests = [
+            (PlayControl::pause_msg(), "pause"),
+            (PlayControl::play_msg(), "play"),
+            (PlayControl::stop_msg(), "stop"),
+            (PlayControl::toggle_playback_msg(), "toggle"),
+        ];
+
+        for (request, action) in requests {
+            assert_round_trip(request, json!({"path": "/zone/play_control", "params": {"action": action}}));
+        }
+    }
+
+    #[test]
+    fn test_play_control_skip_track_msgs() {
+        assert_round_trip(
+            PlayControl::next_track_msg(),
+            json!({"path": "/zone/play_control", "params": {"skip_track": 1}}),
+        );
+        assert_round_trip(
+            PlayControl::previous_track_msg(),
+            json!({"path": "/zone/play_control", "params": {"skip_track": -1}}),
+        );
+    }
+
+    #[test]
+    fn test_play_control_play_queue_id_msg() {
+        assert_round_trip(
+            PlayControl::play_queue_id_msg(42),
+            json!({"path": "/zone/play_control", "params": {"queue_id": 42}}),
+        );
+    }
+
+    #[test]
+    fn test_play_control_seek_msg() {
+        assert_round_trip(
+            PlayControl::seek_msg(95),
+            json!({"path": "/zone/play_control", "params": {"position": 95}}),
+        );
+    }
+
+    #[test]
+    fn test_play_control_seek_percent_msg() {
+        assert_round_trip(
+            PlayControl::seek_percent_msg(25, 200),
+            json!({"path": "/zone/play_control", "params": {"position": 50}}),
+        );
+        assert_round_trip(
+            PlayControl::seek_percent_msg(150, 200),
+            json!({"path": "/zone/play_control", "params": {"position": 200}}),
+        );
+    }
+
+    #[test]
+    fn test_play_control_seek_relative_msg() {
+        assert_round_trip(
+            PlayControl::seek_relative_msg(100, 30, 200),
+            json!({"path": "/zone/play_control", "params": {"position": 130}}),
+        );
+        assert_round_trip(
+            PlayControl::seek_relative_msg(10, -15, 200),
+            json!({"path": "/zone/play_control", "params": {"position": 0}}),
+        );
+        assert_round_trip(
+            PlayControl::seek_relative_msg(190, 30, 200),
+            json!({"path": "/zone/play_control", "params": {"position": 200}}),
+        );
+    }
+
+    #[test]
+    fn test_play_control_repeat_and_shuffle_msgs() {
+        assert_round_trip(
+            PlayControl::set_repeat_msg(TransportToggleState::All),
+            json!({"path": "/zone/play_control", "params": {"mode_repeat": "all"}}),
+        );
+        assert_round_trip(
+            PlayControl::set_shuffle_msg(TransportToggleState::Off),
+            json!({"path": "/zone/play_control", "params": {"mode_shuffle": "off"}}),
+        );
+    }
+
+    // QueueControl
+
+    #[test]
+    fn test_queue_control_add_msgs() {
+        assert_round_trip(
+            QueueControl::add_msg(
+                String::from("http://example.com/track.flac"),
+                Some(String::from("Track")),
+                QueueAddPosition::Next,
+            ),
+            json!({
+                "path": "/queue/add",
+                "params": {"url": "http://example.com/track.flac", "title": "Track", "position": "next"}
+            }),
+        );
+        assert_round_trip(
+            QueueControl::add_msg(String::from("http://example.com/track.flac"), None, QueueAddPosition::End),
+            json!({"path": "/queue/add", "params": {"url": "http://example.com/track.flac", "position": "end"}}),
+        );
+    }
+
+    #[test]
+    fn test_queue_control_clear_msg() {
+        assert_round_trip(QueueControl::clear_msg(), json!({"path": "/queue/clear", "params": {}}));
+    }
+
+    #[test]
+    fn test_queue_control_move_id_msg() {
+        assert_round_trip(
+            QueueControl::move_id_msg(12, 3),
+            json!({"path": "/queue/move", "params": {"id": 12, "position": 3}}),
+        );
+    }
+
+    #[test]
+    fn test_queue_control_remove_ids_msg() {
+        assert_round_trip(
+            QueueControl::remove_ids_msg(vec![4, 8, 15]),
+            json!({"path": "/queue/delete", "params": {"ids": [4, 8, 15]}}),
+        );
+    }
+
+    // RecallPreset
+
+    #[test]
+    fn test_recall_preset_msg() {
+        assert_round_trip(
+            RecallPreset::play_preset_id_msg(7),
+            json!({"path": "/zone/recall_preset", "params": {"preset": 7}}),
+        );
+    }
+
+    // PresetControl
+
+    #[test]
+    fn test_preset_control_msgs() {
+        assert_round_trip(
+            PresetControl::store_preset_id_msg(3),
+            json!({"path": "/presets/store", "params": {"preset": 3}}),
+        );
+        assert_round_trip(
+            PresetControl::clear_preset_id_msg(3),
+            json!({"path": "/presets/delete", "params": {"preset": 3}}),
+        );
+        assert_round_trip(
+            PresetControl::rename_preset_id_msg(3, "Radio 3".to_string()),
+            json!({"path": "/presets/rename", "params": {"preset": 3, "name": "Radio 3"}}),
+        );
+    }
+
+    // StreamRadio
+
+    #[test]
+    fn test_stream_radio_msgs() {
+        assert_round_trip(
+            StreamRadio::play_url_msg("http://radio.example/stream".to_string(), "Example FM".to_string(), None),
+            json!({
+                "path": "/stream/radio",
+                "params": {"url": "http://radio.example/stream", "name": "Example FM"},
+            }),
+        );
+        assert_round_trip(
+            StreamRadio::play_url_msg(
+                "http://radio.example/stream".to_string(),
+                "Example FM".to_string(),
+                Some("http://radio.example/art.png".to_string()),
+            ),
+            json!({
+                "path": "/stream/radio",
+                "params": {
+                    "url": "http://radio.example/stream",
+                    "name": "Example FM",
+                    "art_url": "http://radio.example/art.png",
+                },
+            }),
+        );
+        assert_round_trip(
+            StreamRadio::play_airable_radio_id_msg(1234567890123),
+            json!({"path": "/stream/radio", "params": {"airable_radio_id": 1234567890123_i64}}),
+        );
+    }
+
+    // SystemPower
+
+    #[test]
+    fn test_system_power_msgs() {
+        let requests = [
+            (SystemPower::on_msg(), "ON"),
+            (SystemPower::standby_msg(), "NETWORK"),
+            (SystemPower::toggle_msg(), "toggle"),
+        ];
+
+        for (request, power) in requests {
+            assert_round_trip(request, json!({"path": "/system/power", "params": {"power": power}}));
+        }
+    }
+
+    // SystemDisplay
+
+    #[test]
+    fn test_system_display_brightness_msgs() {
+        let requests = [
+            (SystemDisplay::set_brightness_msg(DisplayBrightness::Bright), "bright"),
+            (SystemDisplay::set_brightness_msg(DisplayBrightness::Dim), "dim"),
+            (SystemDisplay::set_brightness_msg(DisplayBrightness::Off), "off"),
+        ];
+
+        for (request, brightness) in requests {
+            assert_round_trip(
+                request,
+                json!({"path": "/system/display", "params": {"brightness": brightness}}),
+            );
+        }
+    }
+
+    #[test]
+    fn test_system_display_payload() {
+        let payload: StreamerSystemDisplay =
+            serde_json::from_value(json!({"data": {"brightness": "dim"}})).unwrap();
+
+        assert_eq!(payload.0.data.brightness, Some(DisplayBrightness::Dim));
+
+        let payload: StreamerSystemDisplay =
+            serde_json::from_value(json!({"data": {"brightness": "auto"}})).unwrap();
+
+        assert_eq!(payload.0.data.brightness, Some(DisplayBrightness::Unknown));
+    }
+
+    // SystemSources
+
+    #[test]
+    fn test_system_sources_msgs() {
+        assert_round_trip(
+            SystemSources::rename_source_msg("AIRPLAY".to_string(), "AirPlay (Lounge)".to_string()),
+            json!({"path": "/system/sources", "params": {"sources": [{"id": "AIRPLAY", "name": "AirPlay (Lounge)"}]}}),
+        );
+        assert_round_trip(
+            SystemSources::set_source_visible_msg("SPOTIFY".to_string(), false),
+            json!({"path": "/system/sources", "params": {"sources": [{"id": "SPOTIFY", "ui_selectable": false}]}}),
+        );
+        assert_round_trip(
+            SystemSources::reorder_sources_msg(vec!["TIDAL".to_string(), "AIRPLAY".to_string()]),
+            json!({
+                "path": "/system/sources",
+                "params": {"sources": [
+                    {"id": "TIDAL", "preferred_order": 0},
+                    {"id": "AIRPLAY", "preferred_order": 1},
+                ]},
+            }),
+        );
+    }
+
+    // SystemUpdate
+
+    #[test]
+    fn test_system_update_action_msgs() {
+        let requests = [
+            (SystemUpdate::action_msg(SystemUpdateAction::Check), "check"),
+            (SystemUpdate::action_msg(SystemUpdateAction::Start), "start"),
+        ];
+
+        for (request, action) in requests {
+            assert_round_trip(request, json!({"path": "/system/update", "params": {"action": action}}));
+        }
+    }
+
+    #[test]
+    fn test_system_update_payload() {
+        let payload: StreamerSystemUpdate = serde_json::from_value(json!({
+            "data": {"update_available": true, "updating": true, "state": "downloading", "progress": 42}
+        }))
+        .unwrap();
+
+        assert_eq!(payload.0.data.update_available, Some(true));
+        assert_eq!(payload.0.data.progress, Some(42));
+        assert_eq!(payload.0.data.version, None);
+    }
+
+    // ZoneState
+
+    #[test]
+    fn test_zone_state_volume_and_mute_msgs() {
+        assert_round_trip(
+            ZoneState::set_mute_msg(true),
+            json!({"path": "/zone/state", "params": {"mute": true}}),
+        );
+        assert_round_trip(
+            ZoneState::set_volume_percent_msg(35),
+            json!({"path": "/zone/state", "params": {"volume_percent": 35}}),
+        );
+        assert_round_trip(
+            ZoneState::change_volume_step_msg(-2),
+            json!({"path": "/zone/state", "params": {"volume_step_change": -2}}),
+        );
+        assert_round_trip(
+            ZoneState::set_volume_step_msg(40),
+            json!({"path": "/zone/state", "params": {"volume_step": 40}}),
+        );
+    }
+
+    #[test]
+    fn test_zone_state_mode_msgs() {
+        assert_round_trip(
+            ZoneState::request_state_msg(),
+            json!({"path": "/zone/state", "params": {}}),
+        );
+        assert_round_trip(
+            ZoneState::set_pre_amp_mode_msg(true),
+            json!({"path": "/zone/state", "params": {"pre_amp_mode": true}}),
+        );
+        assert_round_trip(
+            ZoneState::set_control_bus_mode_msg(ControlBusMode::Amplifier),
+            json!({"path": "/zone/state", "params": {"cbus": "amplifier"}}),
+        );
+    }
+
+    #[test]
+    fn test_zone_state_set_source_id_msg_escapes_source() {
+        let source_id = String::from(r#"MEDIA_"PLAYER"\1"#);
+
+        assert_round_trip(
+            ZoneState::set_source_id_msg(source_id.clone()),
+            json!({"path": "/zone/state", "params": {"source": source_id}}),
+        );
+    }
+
+    // ZoneAudio
+
+    #[test]
+    fn test_zone_audio_msgs() {
+        assert_round_trip(
+            ZoneAudio::set_balance_msg(-3),
+            json!({"path": "/zone/audio", "params": {"balance": -3}}),
+        );
+        assert_round_trip(
+            ZoneAudio::set_tone_bass_msg(4),
+            json!({"path": "/zone/audio", "params": {"tone_bass": 4}}),
+        );
+        assert_round_trip(
+            ZoneAudio::set_tone_treble_msg(-2),
+            json!({"path": "/zone/audio", "params": {"tone_treble": -2}}),
+        );
+        assert_round_trip(
+            ZoneAudio::set_user_eq_enabled_msg(true),
+            json!({"path": "/zone/audio", "params": {"user_eq": true}}),
+        );
+        assert_round_trip(
+            ZoneAudio::set_user_eq_band_gain_msg(2, -1.5),
+            json!({"path": "/zone/audio", "params": {"user_eq_bands": [{"index": 2, "gain": -1.5}]}}),
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Tracks outgoing StreamMagic requests until the streamer replies to them.
+//!
+//! StreamMagic replies do not carry a request id. Instead, the streamer replies to each request
+//! with a `response` message on the same path, in the order the requests were sent. Pending
+//! requests are therefore tracked in a FIFO queue per path, and each response resolves the oldest
+//! pending request for its path.
+//!
+//! Every outgoing request is tracked (not just those originating from a `StreamerAction`), so that
+//! responses to internal requests (such as update registrations) don't resolve the wrong request.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::streammagic_manager::StreamerAction;
+
+#[derive(Clone, Debug)]
+pub struct PendingRequest {
+    // The StreamerAction which resulted in the request, if any
+    pub action: Option<StreamerAction>,
+    pub path: String,
+    pub sent_at: Instant,
+}
+
+pub struct PendingRequests {
+    by_path: HashMap<String, VecDeque<PendingRequest>>,
+    timeout: Duration,
+}
+
+impl PendingRequests {
+    pub fn new(timeout: Duration) -> Self {
+        PendingRequests {
+            by_path: HashMap::new(),
+            timeout,
+        }
+    }
+
+    /// Track a request which has just been sent to the streamer.
+    pub fn add(&mut self, path: &str, action: Option<StreamerAction>) {
+        self.by_path
+            .entry(path.to_string())
+            .or_default()
+            .push_back(PendingRequest {
+                action,
+                path: path.to_string(),
+                sent_at: Instant::now(),
+            });
+    }
+
+    /// Resolve the oldest pending request for the given path (called when a response is received).
+    pub fn resolve(&mut self, path: &str) -> Option<PendingRequest> {
+        let queue = self.by_path.get_mut(path)?;
+        let resolved = queue.pop_front();
+
+        if queue.is_empty() {
+            self.by_path.remove(path);
+        }
+
+        resolved
+    }
+
+    /// Remove and return all pending requests which have been waiting longer than the timeout.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<PendingRequest> {
+        let mut expired = Vec::new();
+
+        for queue in self.by_path.values_mut() {
+            // Requests are queued in the order they were sent, so only the front of each queue
+            // needs to be checked.
+            while queue.front().is_some_and(|oldest| now.duration_since(oldest.sent_at) > self.timeout) {
+                if let Some(request) = queue.pop_front() {
+                    expired.push(request);
+                }
+            }
+        }
+
+        self.by_path.retain(|_, queue| !queue.is_empty());
+
+        expired
+    }
+
+    /// Remove and return all pending requests (e.g. when the connection has been lost).
+    pub fn take_all(&mut self) -> Vec<PendingRequest> {
+        self.by_path.drain().flat_map(|(_, queue)| queue).collect()
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_oldest_request_for_path() {
+        let mut pending = PendingRequests::new(Duration::from_secs(5));
+
+        pending.add("/zone/play_control", Some(StreamerAction::Play));
+        pending.add("/zone/state", None);
+        pending.add("/zone/play_control", Some(StreamerAction::Pause));
+
+        let first = pending.resolve("/zone/play_control").unwrap();
+        assert!(matches!(first.action, Some(StreamerAction::Play)));
+
+        let second = pending.resolve("/zone/play_control").unwrap();
+        assert!(matches!(second.action, Some(StreamerAction::Pause)));
+
+        assert!(pending.resolve("/zone/play_control").is_none());
+        assert!(pending.resolve("/zone/state").is_some());
+    }
+
+    #[test]
+    fn test_takes_expired_requests() {
+        let mut pending = PendingRequests::new(Duration::from_secs(5));
+
+        pending.add("/zone/recall_preset", Some(StreamerAction::PlayPresetId(1)));
+
+        assert!(pending.take_expired(Instant::now()).is_empty());
+
+        let later = Instant::now() + Duration::from_secs(6);
+        let expired = pending.take_expired(later);
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].path, "/zone/recall_preset");
+        assert!(pending.resolve("/zone/recall_preset").is_none());
+    }
+
+    #[test]
+    fn test_takes_all_requests() {
+        let mut pending = PendingRequests::new(Duration::from_secs(5));
+
+        pending.add("/zone/play_control", Some(StreamerAction::Stop));
+        pending.add("/zone/state", Some(StreamerAction::MuteSet(true)));
+
+        assert_eq!(pending.take_all().len(), 2);
+        assert!(pending.take_all().is_empty());
+    }
+}
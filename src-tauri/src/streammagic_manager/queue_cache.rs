@@ -0,0 +1,448 @@
+//! Assembles a complete `QueueList` from paged `/queue/list` responses.
+//!
+//! The streamer only returns a single page of queue items per `/queue/list` request, so large
+//! queues need to be retrieved a page at a time using `start`/`count`. `QueueInfo` provides the
+//! ids of every item in the queue (in queue order), which allows the cache to only request the
+//! pages containing items it hasn't seen yet. When `QueueInfo` ids are not available, the cache
+//! falls back to paging through the entire queue until `total` is reached. A complete queue isn't
+//! paged through again until its token or length changes.
+//!
+//! Cached items are keyed by queue item id. Items which are no longer in the queue are dropped
+//! whenever new `QueueInfo` ids are received. Without ids, every cached item is dropped whenever
+//! the queue's token or length changes.
+//!
+//! Requested pages are tracked until they're received. A page isn't requested again while it's in
+//! flight, but it is once it has timed out (otherwise a single lost page would prevent the queue
+//! from ever being assembled).
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::streammagic_manager::payloads::{QueueInfo, QueueList, QueueListItem, QueueListParams};
+
+pub struct QueueCache {
+    items: HashMap<i64, QueueListItem>,
+    // Queue item ids in queue order, as provided by the most recent QueueInfo
+    ids: Option<Vec<i64>>,
+    id_array_token: Option<i64>,
+    total: Option<i32>,
+    play_id: Option<i32>,
+    play_position: Option<i32>,
+    // Requested pages which haven't been received yet: start position -> (count, requested at)
+    in_flight: HashMap<i32, (i32, Instant)>,
+    page_size: i32,
+    page_timeout: Duration,
+}
+
+impl QueueCache {
+    pub fn new(page_size: i32, page_timeout: Duration) -> Self {
+        QueueCache {
+            items: HashMap::new(),
+            ids: None,
+            id_array_token: None,
+            total: None,
+            play_id: None,
+            play_position: None,
+            in_flight: HashMap::new(),
+            page_size,
+            page_timeout,
+        }
+    }
+
+    /// Forget everything (e.g. when connecting to a new streamer).
+    pub fn reset(&mut self) {
+        *self = QueueCache::new(self.page_size, self.page_timeout);
+    }
+
+    /// Store the latest QueueInfo, and return the pages which need to be requested to retrieve any
+    /// queue items which have not been seen yet.
+    pub fn set_queue_info(&mut self, queue_info: &QueueInfo, now: Instant) -> Vec<QueueListParams> {
+        self.play_id = queue_info.play_id.map(|id| id as i32);
+        self.play_position = queue_info.play_postition.map(|position| position as i32);
+
+        let Some(ids) = &queue_info.ids else {
+            // Without ids we can't know which items are missing, so page through the whole queue.
+            // Items from a different queue would otherwise count towards the total.
+            let total = queue_info.total.map(|total| total as i32);
+            let is_same_queue =
+                self.ids.is_none() && total == self.total && queue_info.id_array_token == self.id_array_token;
+
+            if !is_same_queue {
+                self.items.clear();
+                self.in_flight.clear();
+            }
+
+            self.ids = None;
+            self.id_array_token = queue_info.id_array_token;
+            self.total = total;
+
+            if is_same_queue && self.assembled().is_some() {
+                return self.pages_to_request(now);
+            }
+
+            let first_page = QueueListParams {
+                start: Some(0),
+                count: Some(self.page_size),
+            };
+
+            return match self.in_flight.contains_key(&0) {
+                true => self.pages_to_request(now),
+                false => self.mark_in_flight(vec![first_page], now),
+            };
+        };
+
+        if self.ids.is_some() && queue_info.id_array_token.is_some() && queue_info.id_array_token == self.id_array_token
+        {
+            // The queue's ids have not changed since we last saw them
+            return self.pages_to_request(now);
+        }
+
+        let current_ids: HashSet<i64> = ids.iter().copied().collect();
+        self.items.retain(|id, _| current_ids.contains(id));
+
+        // Positions have changed, so the pages in flight no longer line up with the queue
+        if ids.len() as i32 != self.total.unwrap_or_default() {
+            self.in_flight.clear();
+        }
+
+        self.ids = Some(ids.clone());
+        self.id_array_token = queue_info.id_array_token;
+        self.total = Some(ids.len() as i32);
+
+        self.pages_to_request(now)
+    }
+
+    /// The pages which should be requested now: pages with missing items which aren't already in
+    /// flight, and pages which have timed out. When the queue's ids aren't known, only timed out
+    /// pages are requested again (the next page is requested as each page arrives).
+    pub fn pages_to_request(&mut self, now: Instant) -> Vec<QueueListParams> {
+        let timed_out = self.take_timed_out_pages(now);
+
+        let pages = match self.ids {
+            Some(_) => self.missing_pages(),
+            None => timed_out,
+        };
+
+        self.mark_in_flight(pages, now)
+    }
+
+    /// Merge a page of queue items into the cache. Returns the next page to request, if paging
+    /// through the entire queue (i.e. when QueueInfo ids are not known).
+    pub fn add_queue_list(&mut self, queue_list: QueueList, now: Instant) -> Option<QueueListParams> {
+        if queue_list.play_id.is_some() {
+            self.play_id = queue_list.play_id;
+            self.play_position = queue_list.play_postition;
+        }
+
+        let start = queue_list.start.unwrap_or(0);
+        self.in_flight.remove(&start);
+        let items = queue_list.items.unwrap_or_default();
+        let received_count = items.len() as i32;
+
+        for (index, item) in items.into_iter().enumerate() {
+            if let Some(id) = item.id {
+                let mut item = item;
+
+                if item.position.is_none() {
+                    item.position = Some(start + index as i32);
+                }
+
+                self.items.insert(id as i64, item);
+            }
+        }
+
+        if self.ids.is_some() {
+            // The required pages were already requested when the QueueInfo was received
+            return None;
+        }
+
+        if let Some(total) = queue_list.total {
+            self.total = Some(total);
+        }
+
+        let next_start = start + received_count;
+
+        let next_page = match self.total {
+            Some(total) if received_count > 0 && next_start < total => QueueListParams {
+                start: Some(next_start),
+                count: Some(self.page_size.min(total - next_start)),
+            },
+            _ => return None,
+        };
+
+        self.mark_in_flight(vec![next_page], now).pop()
+    }
+
+    /// The complete queue, if every item in the queue has been retrieved.
+    pub fn assembled(&self) -> Option<QueueList> {
+        let items: Vec<QueueListItem> = match &self.ids {
+            Some(ids) => {
+                let mut items = Vec::with_capacity(ids.len());
+
+                for (position, id) in ids.iter().enumerate() {
+                    let mut item = self.items.get(id)?.clone();
+                    item.position = Some(position as i32);
+                    items.push(item);
+                }
+
+                items
+            }
+            None => {
+                let total = self.total?;
+
+                if (self.items.len() as i32) < total {
+                    return None;
+                }
+
+                let mut items: Vec<QueueListItem> = self.items.values().cloned().collect();
+                items.sort_by_key(|item| item.position);
+
+                items
+            }
+        };
+
+        Some(QueueList {
+            start: Some(0),
+            count: Some(items.len() as i32),
+            total: Some(items.len() as i32),
+            play_postition: self.play_position,
+            play_id: self.play_id,
+            items: Some(items),
+        })
+    }
+
+    /// Determine the pages which contain the queue ids we haven't yet retrieved (and which aren't
+    /// already in flight). Each page starts at the first missing position it needs to cover, so
+    /// scattered missing items share pages where possible.
+    fn missing_pages(&self) -> Vec<QueueListParams> {
+        let Some(ids) = &self.ids else {
+            return Vec::new();
+        };
+
+        let mut pages = Vec::new();
+        let mut next_uncovered = 0;
+
+        for (position, id) in ids.iter().enumerate() {
+            let position = position as i32;
+
+            if position < next_uncovered || self.items.contains_key(id) || self.is_in_flight(position) {
+                continue;
+            }
+
+            let count = self.page_size.min(ids.len() as i32 - position);
+            pages.push(QueueListParams {
+                start: Some(position),
+                count: Some(count),
+            });
+            next_uncovered = position + count;
+        }
+
+        pages
+    }
+
+    fn is_in_flight(&self, position: i32) -> bool {
+        self.in_flight
+            .iter()
+            .any(|(start, (count, _))| position >= *start && position < start + count)
+    }
+
+    fn mark_in_flight(&mut self, pages: Vec<QueueListParams>, now: Instant) -> Vec<QueueListParams> {
+        for page in &pages {
+            self.in_flight
+                .insert(page.start.unwrap_or(0), (page.count.unwrap_or(self.page_size), now));
+        }
+
+        pages
+    }
+
+    /// Stop waiting for pages which have been in flight for longer than the page timeout,
+    /// returning them.
+    fn take_timed_out_pages(&mut self, now: Instant) -> Vec<QueueListParams> {
+        let page_timeout = self.page_timeout;
+        let mut timed_out = Vec::new();
+
+        self.in_flight.retain(|start, (count, requested_at)| {
+            let has_timed_out = now.duration_since(*requested_at) > page_timeout;
+
+            if has_timed_out {
+                timed_out.push(QueueListParams {
+                    start: Some(*start),
+                    count: Some(*count),
+                });
+            }
+
+            !has_timed_out
+        });
+
+        timed_out.sort_by_key(|page| page.start);
+
+        timed_out
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn queue_info(ids: Vec<i64>, token: i64) -> QueueInfo {
+        QueueInfo {
+            total: Some(ids.len() as i64),
+            ids: Some(ids),
+            id_array_token: Some(token),
+            play_postition: None,
+            play_id: None,
+        }
+    }
+
+    fn queue_list(start: i32, ids: &[i32], total: i32) -> QueueList {
+        QueueList {
+            start: Some(start),
+            count: Some(ids.len() as i32),
+            total: Some(total),
+            play_postition: None,
+            play_id: None,
+            items: Some(
+                ids.iter()
+                    .map(|id| QueueListItem {
+                        id: Some(*id),
+                        position: None,
+                        metadata: None,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn assembled_ids(cache: &QueueCache) -> Vec<i32> {
+        cache
+            .assembled()
+            .expect("Queue should be complete")
+            .items
+            .unwrap()
+            .iter()
+            .map(|item| item.id.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_requests_pages_until_all_ids_are_known() {
+        let mut cache = QueueCache::new(2, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        let pages = cache.set_queue_info(&queue_info(vec![10, 11, 12, 13, 14], 1), now);
+        let starts: Vec<Option<i32>> = pages.iter().map(|page| page.start).collect();
+        assert_eq!(starts, vec![Some(0), Some(2), Some(4)]);
+        assert_eq!(pages[2].count, Some(1));
+
+        cache.add_queue_list(queue_list(0, &[10, 11], 5), now);
+        cache.add_queue_list(queue_list(2, &[12, 13], 5), now);
+        assert!(cache.assembled().is_none());
+
+        cache.add_queue_list(queue_list(4, &[14], 5), now);
+        assert_eq!(assembled_ids(&cache), vec![10, 11, 12, 13, 14]);
+    }
+
+    #[test]
+    fn test_only_requests_unseen_ids() {
+        let mut cache = QueueCache::new(2, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        cache.set_queue_info(&queue_info(vec![10, 11, 12, 13], 1), now);
+        cache.add_queue_list(queue_list(0, &[10, 11], 4), now);
+        cache.add_queue_list(queue_list(2, &[12, 13], 4), now);
+
+        // 11 has been removed and 20 has been added to the end
+        let pages = cache.set_queue_info(&queue_info(vec![10, 12, 13, 20], 2), now);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].start, Some(3));
+        assert_eq!(pages[0].count, Some(1));
+
+        cache.add_queue_list(queue_list(3, &[20], 4), now);
+        assert_eq!(assembled_ids(&cache), vec![10, 12, 13, 20]);
+    }
+
+    #[test]
+    fn test_reorders_cached_items_without_requests() {
+        let mut cache = QueueCache::new(10, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        cache.set_queue_info(&queue_info(vec![1, 2, 3], 1), now);
+        cache.add_queue_list(queue_list(0, &[1, 2, 3], 3), now);
+
+        let pages = cache.set_queue_info(&queue_info(vec![3, 1, 2], 2), now);
+        assert!(pages.is_empty());
+        assert_eq!(assembled_ids(&cache), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_pages_through_queue_without_ids() {
+        let mut cache = QueueCache::new(2, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        let next = cache.add_queue_list(queue_list(0, &[1, 2], 3), now).unwrap();
+        assert_eq!(next.start, Some(2));
+        assert_eq!(next.count, Some(1));
+        assert!(cache.assembled().is_none());
+
+        assert!(cache.add_queue_list(queue_list(2, &[3], 3), now).is_none());
+        assert_eq!(assembled_ids(&cache), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_does_not_rerequest_pages_in_flight() {
+        let mut cache = QueueCache::new(2, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        assert_eq!(cache.set_queue_info(&queue_info(vec![1, 2, 3, 4], 1), now).len(), 2);
+        cache.add_queue_list(queue_list(0, &[1, 2], 4), now);
+
+        // The second page is still in flight
+        assert!(cache.set_queue_info(&queue_info(vec![1, 2, 3, 4], 1), now).is_empty());
+        assert!(cache.pages_to_request(now + PAGE_TIMEOUT).is_empty());
+
+        // The second page never arrived, so it's requested again
+        let pages = cache.pages_to_request(now + Duration::from_secs(6));
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].start, Some(2));
+        assert!(cache.pages_to_request(now + Duration::from_secs(7)).is_empty());
+
+        cache.add_queue_list(queue_list(2, &[3, 4], 4), now + Duration::from_secs(7));
+        assert_eq!(assembled_ids(&cache), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_clears_items_when_queue_without_ids_changes() {
+        let mut cache = QueueCache::new(10, PAGE_TIMEOUT);
+        let now = Instant::now();
+
+        let queue_info_without_ids = |total: i64, token: i64| QueueInfo {
+            total: Some(total),
+            ids: None,
+            id_array_token: Some(token),
+            play_postition: None,
+            play_id: None,
+        };
+
+        cache.set_queue_info(&queue_info_without_ids(3, 1), now);
+        cache.add_queue_list(queue_list(0, &[1, 2, 3], 3), now);
+        assert_eq!(assembled_ids(&cache), vec![1, 2, 3]);
+
+        // Items from the previous queue don't count towards the new queue's total
+        let pages = cache.set_queue_info(&queue_info_without_ids(2, 2), now);
+        assert_eq!(pages[0].start, Some(0));
+        assert!(cache.assembled().is_none());
+
+        cache.add_queue_list(queue_list(0, &[7, 8], 2), now);
+        assert_eq!(assembled_ids(&cache), vec![7, 8]);
+
+        // An unchanged queue which has already been assembled isn't requested again
+        assert!(cache.set_queue_info(&queue_info_without_ids(2, 2), now).is_empty());
+        assert_eq!(assembled_ids(&cache), vec![7, 8]);
+    }
+}
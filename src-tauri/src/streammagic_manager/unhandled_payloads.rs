@@ -0,0 +1,334 @@
+//! Retains StreamMagic data which PunyTunes doesn't (yet) know how to handle.
+//!
+//! Two kinds of data are captured:
+//!
+//! * Messages on paths which the StreamMagicManager doesn't process.
+//! * Fields in processed payloads which the typed payload structs don't capture. These are found
+//!   by comparing the raw payload against the typed payload serialized back to JSON. Some payloads
+//!   arrive every second (e.g. position updates), so the comparison is only made the first time
+//!   each shape of payload (i.e. its set of fields) is seen on a path.
+//!
+//! Captured data is retained per path, with only the most recent entries for each path being
+//! kept. The number of paths is also limited, with the least recently captured path being dropped
+//! to make room for a new one. This allows new firmware paths and fields to be inspected from the
+//! UI.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use ts_rs::TS;
+
+use crate::utils::epoch_millis_now;
+
+// Number of payload shapes remembered per path before they're forgotten (and checked again)
+const MAX_SHAPES_PER_PATH: usize = 50;
+
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/UnhandledPayloadKind.ts")]
+pub enum UnhandledPayloadKind {
+    // The whole message, for a path which is not processed
+    UnhandledPath,
+    // Only the fields which the typed payload did not capture
+    UnknownFields,
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/UnhandledPayload.ts")]
+pub struct UnhandledPayload {
+    pub path: String,
+    pub kind: UnhandledPayloadKind,
+    #[ts(type = "any")]
+    pub data: Value,
+    when: u128,
+}
+
+pub struct UnhandledPayloads {
+    by_path: BTreeMap<String, VecDeque<UnhandledPayload>>,
+    // Paths with captured data, least recently captured first
+    paths_by_recency: VecDeque<String>,
+    // Hashes of the payload shapes which have already been checked for unknown fields, per path
+    checked_shapes: HashMap<String, HashSet<u64>>,
+    max_paths: usize,
+    max_per_path: usize,
+    // Whether anything has been captured since the last call to take_has_changed()
+    has_changed: bool,
+}
+
+impl UnhandledPayloads {
+    pub fn new(max_paths: usize, max_per_path: usize) -> Self {
+        UnhandledPayloads {
+            by_path: BTreeMap::new(),
+            paths_by_recency: VecDeque::new(),
+            checked_shapes: HashMap::new(),
+            max_paths,
+            max_per_path,
+            has_changed: false,
+        }
+    }
+
+    /// Capture a message on a path which is not processed.
+    pub fn add_unhandled_path(&mut self, path: &str, message: Value) {
+        self.add(path, UnhandledPayloadKind::UnhandledPath, message);
+    }
+
+    /// Capture any fields in a raw payload which are not present in its typed form. The typed form
+    /// is only requested if this shape of payload hasn't already been checked on this path.
+    pub fn add_unknown_fields(&mut self, path: &str, raw: &Value, typed: impl FnOnce() -> Option<Value>) {
+        let shapes = self.checked_shapes.entry(path.to_string()).or_default();
+
+        if shapes.len() >= MAX_SHAPES_PER_PATH {
+            shapes.clear();
+        }
+
+        if !shapes.insert(shape_hash(raw)) {
+            return;
+        }
+
+        if let Some(unknown) = typed().and_then(|typed| unknown_fields(raw, &typed)) {
+            self.add(path, UnhandledPayloadKind::UnknownFields, unknown);
+        }
+    }
+
+    /// All captured payloads, ordered by path and then oldest first.
+    pub fn all(&self) -> Vec<UnhandledPayload> {
+        self.by_path.values().flatten().cloned().collect()
+    }
+
+    /// Whether anything has been captured since this was last called.
+    pub fn take_has_changed(&mut self) -> bool {
+        std::mem::take(&mut self.has_changed)
+    }
+
+    fn add(&mut self, path: &str, kind: UnhandledPayloadKind, data: Value) {
+        // Payloads are often repeated, so only capture data which differs from the most recently
+        // captured data of the same kind.
+        let is_repeat = self.by_path.get(path).is_some_and(|entries| {
+            entries
+                .iter()
+                .rev()
+                .find(|entry| entry.kind == kind)
+                .is_some_and(|entry| entry.data == data)
+        });
+
+        if is_repeat {
+            return;
+        }
+
+        self.paths_by_recency.retain(|recent_path| recent_path != path);
+        self.paths_by_recency.push_back(path.to_string());
+
+        while self.paths_by_recency.len() > self.max_paths {
+            if let Some(dropped_path) = self.paths_by_recency.pop_front() {
+                self.by_path.remove(&dropped_path);
+            }
+        }
+
+        let entries = self.by_path.entry(path.to_string()).or_default();
+
+        entries.push_back(UnhandledPayload {
+            path: path.to_string(),
+            kind,
+            data,
+            when: epoch_millis_now(),
+        });
+
+        while entries.len() > self.max_per_path {
+            entries.pop_front();
+        }
+
+        self.has_changed = true;
+    }
+}
+
+/// A hash of a payload's shape: its field names (at every level), and which fields are null.
+/// Array items are treated as a set of shapes, so the number of items doesn't change the shape.
+fn shape_hash(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_shape(value, &mut hasher);
+
+    hasher.finish()
+}
+
+fn hash_shape(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Object(fields) => {
+            "{".hash(hasher);
+
+            for (key, field_value) in fields {
+                key.hash(hasher);
+                hash_shape(field_value, hasher);
+            }
+
+            "}".hash(hasher);
+        }
+        Value::Array(items) => {
+            let item_shapes: BTreeSet<u64> = items.iter().map(shape_hash).collect();
+
+            "[".hash(hasher);
+            item_shapes.hash(hasher);
+        }
+        Value::Null => "null".hash(hasher),
+        _ => "value".hash(hasher),
+    }
+}
+
+/// Find the parts of `raw` which are not in `typed`. Objects are compared by key, and arrays by
+/// index (unknown fields in array items are keyed by the item's index). Null raw values are
+/// ignored, as typed payloads may skip serializing fields which are None.
+fn unknown_fields(raw: &Value, typed: &Value) -> Option<Value> {
+    let unknown: Map<String, Value> = match (raw, typed) {
+        (Value::Object(raw), Value::Object(typed)) => raw
+            .iter()
+            .filter_map(|(key, raw_value)| match typed.get(key) {
+                Some(typed_value) => unknown_fields(raw_value, typed_value).map(|unknown| (key.clone(), unknown)),
+                None if raw_value.is_null() => None,
+                None => Some((key.clone(), raw_value.clone())),
+            })
+            .collect(),
+        (Value::Array(raw), Value::Array(typed)) => raw
+            .iter()
+            .zip(typed)
+            .enumerate()
+            .filter_map(|(index, (raw_value, typed_value))| {
+                unknown_fields(raw_value, typed_value).map(|unknown| (index.to_string(), unknown))
+            })
+            .collect(),
+        _ => return None,
+    };
+
+    if unknown.is_empty() {
+        None
+    } else {
+        Some(Value::Object(unknown))
+    }
+}
+
+// ================================================================================================
+// Tests
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_finds_unknown_fields() {
+        let raw = json!({
+            "zone": "ZONE1",
+            "data": {
+                "source": "AIRPLAY",
+                "new_field": 42,
+                "skipped": null,
+                "nested": {"known": 1, "also_new": true},
+                "items": [{"id": 1}, {"id": 2, "extra": "x"}],
+            },
+        });
+        let typed = json!({
+            "zone": "ZONE1",
+            "data": {
+                "source": "AIRPLAY",
+                "nested": {"known": 1},
+                "items": [{"id": 1}, {"id": 2}],
+            },
+        });
+
+        assert_eq!(
+            unknown_fields(&raw, &typed),
+            Some(json!({
+                "data": {
+                    "new_field": 42,
+                    "nested": {"also_new": true},
+                    "items": {"1": {"extra": "x"}},
+                },
+            }))
+        );
+
+        assert_eq!(unknown_fields(&typed, &typed), None);
+    }
+
+    #[test]
+    fn test_ignores_repeated_payloads() {
+        let mut unhandled = UnhandledPayloads::new(5, 5);
+
+        unhandled.add_unhandled_path("/new/path", json!({"a": 1}));
+        assert!(unhandled.take_has_changed());
+
+        unhandled.add_unhandled_path("/new/path", json!({"a": 1}));
+        assert!(!unhandled.take_has_changed());
+        assert_eq!(unhandled.all().len(), 1);
+
+        unhandled.add_unknown_fields("/zone/state", &json!({"a": 1, "b": 2}), || Some(json!({"a": 1})));
+        unhandled.add_unknown_fields("/zone/state", &json!({"a": 1}), || Some(json!({"a": 1})));
+        assert!(unhandled.take_has_changed());
+
+        let all = unhandled.all();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].path, "/new/path");
+        assert_eq!(all[1].kind, UnhandledPayloadKind::UnknownFields);
+        assert_eq!(all[1].data, json!({"b": 2}));
+    }
+
+    #[test]
+    fn test_retains_most_recent_payloads_per_path() {
+        let mut unhandled = UnhandledPayloads::new(5, 2);
+
+        for value in 0..4 {
+            unhandled.add_unhandled_path("/new/path", json!(value));
+        }
+        unhandled.add_unhandled_path("/other/path", json!(0));
+
+        let values: Vec<Value> = unhandled
+            .all()
+            .into_iter()
+            .filter(|payload| payload.path == "/new/path")
+            .map(|payload| payload.data)
+            .collect();
+
+        assert_eq!(values, vec![json!(2), json!(3)]);
+        assert_eq!(unhandled.all().len(), 3);
+    }
+
+    #[test]
+    fn test_drops_least_recently_captured_path() {
+        let mut unhandled = UnhandledPayloads::new(2, 5);
+
+        unhandled.add_unhandled_path("/first", json!(0));
+        unhandled.add_unhandled_path("/second", json!(0));
+        unhandled.add_unhandled_path("/first", json!(1));
+        unhandled.add_unhandled_path("/third", json!(0));
+
+        let paths: BTreeSet<String> = unhandled.all().into_iter().map(|payload| payload.path).collect();
+
+        assert_eq!(paths, BTreeSet::from(["/first".to_string(), "/third".to_string()]));
+    }
+
+    #[test]
+    fn test_only_checks_each_payload_shape_once() {
+        let mut unhandled = UnhandledPayloads::new(5, 5);
+        let mut checks = 0;
+
+        for position in 0..3 {
+            unhandled.add_unknown_fields("/zone/play_state/position", &json!({"position": position}), || {
+                checks += 1;
+                Some(json!({"position": position}))
+            });
+        }
+
+        unhandled.add_unknown_fields("/zone/play_state/position", &json!({"position": 3, "new": 1}), || {
+            checks += 1;
+            Some(json!({"position": 3}))
+        });
+
+        assert_eq!(checks, 2);
+        assert_eq!(unhandled.all()[0].data, json!({"new": 1}));
+        assert_eq!(
+            shape_hash(&json!({"a": [{"b": 1}]})),
+            shape_hash(&json!({"a": [{"b": 2}, {"b": 3}]}))
+        );
+        assert_ne!(shape_hash(&json!({"a": 1})), shape_hash(&json!({"a": null})));
+    }
+}
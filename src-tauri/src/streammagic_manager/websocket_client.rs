@@ -1,9 +1,5 @@
-// TODO: Consider having WebSocketClient use send_app_log!() instead of app_info!()/etc. This
-//  would ensure that any early logs (before the UI is ready) are buffered properly. It would also
-//  require that WebSocketClient be passed a copy of the mpsc sender to then pass to send_app_log!().
-
 use std::default::Default;
-use std::time::SystemTime;
+use std::time::Instant;
 
 use futures_util::{SinkExt, StreamExt};
 use log::{info, Level::{Error, Info, Warn}};
@@ -18,9 +14,17 @@ use ts_rs::TS;
 use tungstenite;
 use url;
 
-use crate::average::RunningAverage;
 use crate::errors::PunyTunesError;
-use crate::streammagic_manager::StreamMagicManagerChannelMsg;
+use crate::streammagic_manager::connection_quality::{ConnectionQuality, KeepaliveTracker};
+
+// How often to send a keepalive ping to the WebSocket server
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+// How long to wait for a keepalive pong before considering the ping lost
+const KEEPALIVE_PONG_TIMEOUT: Duration = Duration::from_secs(3);
+// Number of consecutive lost keepalive pings after which the connection is considered lost
+const MAX_CONSECUTIVE_PINGS_LOST: usize = 3;
+// Number of recent keepalive round-trip latencies to measure connection quality from
+const KEEPALIVE_LATENCY_WINDOW: usize = 20;
 
 // Channel messages which can be sent from the invoker (StreamMagicManager) to the WebSocketClient
 #[derive(Clone, Debug)]
@@ -35,6 +39,7 @@ pub enum WSClientTxChannelMsg {
     DataMsg(String),
     // WebSocket data text, as received from the StreamMagic WebSocket server
     WebSocketClientStatusMsg(WebSocketClientStatus),
+    ConnectionQualityMsg(ConnectionQuality),
 }
 
 #[derive(Clone, Debug)]
@@ -93,37 +98,34 @@ pub enum WebSocketClientStatus {
 ///   * Sends messages on `tx_channel`
 ///       * `DataMsg`: A plain text message (from the WebSocket server) to send to the invoker.
 ///       * `WebSocketClientStatusMsg`: A client status update.
+///       * `ConnectionQualityMsg`: A connection quality update (based on keepalive pings).
+///   * Sends regular keepalive pings to the WebSocket server, and considers the connection lost
+///     if too many consecutive pings don't receive a pong.
 ///
 /// WebSocketClient does not have any awareness of StreamMagic. Instead, it passes messages back
 /// and forth as plain text. `StreamMagicManager` handles the StreamMagic-specific concepts.
 pub struct WebSocketClient {
     url: String,
-    manager_channel: Sender<StreamMagicManagerChannelMsg>,
     rx_channel: Receiver<WSClientRxChannelMsg>,
     tx_channel: Sender<WSClientTxChannelMsg>,
     status: WebSocketClientStatus,
     connection_timeout_ms: u64,
     test_connection_pong_timeout_ms: u128,
-    stop_on_missing_pings: bool,
 }
 
 impl WebSocketClient {
     pub fn new(
         url: &str,
-        manager_channel: Sender<StreamMagicManagerChannelMsg>,
         rx_channel: Receiver<WSClientRxChannelMsg>,
         tx_channel: Sender<WSClientTxChannelMsg>,
-        stop_on_missing_pings: bool,
     ) -> Self {
         WebSocketClient {
             url: url.to_string(),
-            manager_channel,
             rx_channel,
             tx_channel,
             status: WebSocketClientStatus::Disconnected(Default::default()),
             connection_timeout_ms: 2000,
             test_connection_pong_timeout_ms: 1000,
-            stop_on_missing_pings,
         }
     }
 
@@ -135,21 +137,16 @@ impl WebSocketClient {
         // items waiting in a channel for processing.
         let mut interval = tokio::time::interval(Duration::from_secs(1));
 
-        // Track WebSocket server ping times. This is done to establish when the client may have
-        // lost the connection (perhaps due to going to sleep). This is different from the server
-        // explicitly closing the connection (see tungstenite::Message::Close). The average ping
-        // duration is tracked, and if enough time has passed since the last ping then we assume
-        // the connection is lost. We wait for at least 2 pings to come in before calculating the
-        // average ping delay.
-        let mut ping_avg = RunningAverage::new(10);
-        let mut last_ping_time = SystemTime::now();
-        let mut have_ignored_first_ping = false;
+        // Send regular keepalive pings to the WebSocket server. The pongs are used to measure the
+        // connection quality, and to establish when the client may have lost the connection
+        // (perhaps due to going to sleep). This is different from the server explicitly closing
+        // the connection (see tungstenite::Message::Close).
+        let mut keepalive_interval = tokio::time::interval(KEEPALIVE_INTERVAL);
+        let mut keepalive = KeepaliveTracker::new(KEEPALIVE_LATENCY_WINDOW, KEEPALIVE_PONG_TIMEOUT);
 
-        // Track pong times as part of WebSocketClientStatus::TestingConnection.
-        let mut test_connection_ping_time = SystemTime::now();
-
-        const START_PING_THRESHOLD_SECS: u64 = 600; // For use until average ping time is known
-        const PING_DURATION_BUFFER_FACTOR: f64 = 1.25; // 125% is considered too long to wait
+        // Track pong times as part of WebSocketClientStatus::TestingConnection. Timings use the
+        // monotonic clock, so they're unaffected by wall clock changes (e.g. NTP syncs).
+        let mut test_connection_ping_time = Instant::now();
 
         loop {
             select! {
@@ -161,14 +158,14 @@ impl WebSocketClient {
                                 match ws_write.send(msg.clone().into()).await {
                                     Ok(()) => {},
                                     Err(e) => {
-                                        send_app_log!(&self.manager_channel, Error, "WebSocket send error: {:?}", e);
+                                        send_app_log!(Error, "WebSocket send error: {:?}", e);
                                     } ,
                                 }
                             }
                             WSClientRxChannelMsg::WebSocketClientActionMsg(action_msg) => {
                                 match action_msg {
                                     WebSocketClientAction::ShutDown => {
-                                        send_app_log!(&self.manager_channel, Info, "WebSocketClient shutting down");
+                                        send_app_log!(Info, "WebSocketClient shutting down");
                                         self.set_status(WebSocketClientStatus::Disconnected(
                                             WebSocketClientDisconnectedDetails { reason: None, consider_reconnecting: false }
                                         )).await;
@@ -184,7 +181,7 @@ impl WebSocketClient {
                                                 // checks are performed in interval.tick().
                                                 match ws_write.send(tungstenite::protocol::Message::Ping("PING".into())).await {
                                                     Ok(_) => {
-                                                        test_connection_ping_time = SystemTime::now();
+                                                        test_connection_ping_time = Instant::now();
                                                         self.set_status(WebSocketClientStatus::TestingConnection).await;
                                                         info!("Test connection ping sent");
                                                     },
@@ -203,6 +200,11 @@ impl WebSocketClient {
                                 }
                             }
                         }
+                    } else {
+                        // The manager has dropped its end of the channel (e.g. it's being restarted)
+                        send_app_log!(Warn, "WebSocketClient command channel closed; shutting down");
+
+                        break;
                     }
                 }
 
@@ -210,24 +212,13 @@ impl WebSocketClient {
                     match read_item {
                         Ok(message) => match message {
                             tungstenite::Message::Ping(_) => {
-                                let now = SystemTime::now();
-
-                                // Keep track of how long we're waiting between pings. Ignore the first
-                                // ping because it might throw off the average wait time calculation.
-                                if have_ignored_first_ping {
-                                    match now.duration_since(last_ping_time) {
-                                        Ok(duration) => ping_avg.add(duration.as_secs() as f64),
-                                        _ => {},
-                                    };
-                                } else {
-                                    have_ignored_first_ping = true;
+                                // Server pings are responded to by tungstenite
+                            },
+                            tungstenite::Message::Pong(payload) => {
+                                if keepalive.pong(&payload, Instant::now()) {
+                                    self.send_connection_quality(keepalive.quality()).await;
                                 }
 
-                                last_ping_time = now;
-
-                                info!("Ping average: {}", ping_avg.average());
-                            },
-                            tungstenite::Message::Pong(_) => {
                                 if self.status == WebSocketClientStatus::TestingConnection {
                                     // Streamer responded to our test ping
                                     self.set_status(WebSocketClientStatus::Connected(
@@ -240,10 +231,10 @@ impl WebSocketClient {
                             },
                             tungstenite::Message::Close(_) => {
                                 // Explicit server connection close. This is distinct from the client
-                                // losing the connection for other reasons (which is detected by ping
-                                // time checks).
+                                // losing the connection for other reasons (which is detected by lost
+                                // keepalive pings).
                                 let reason = String::from("WebSocket connection closed by server");
-                                send_app_log!(&self.manager_channel, Warn, "{}", reason.clone());
+                                send_app_log!(Warn, "{}", reason.clone());
                                 self.set_status(WebSocketClientStatus::Disconnected(
                                     WebSocketClientDisconnectedDetails { reason: Some(reason), consider_reconnecting: true }
                                 )).await;
@@ -255,12 +246,12 @@ impl WebSocketClient {
                                 match self.tx_channel.send(WSClientTxChannelMsg::DataMsg(message_text)).await {
                                     Ok(()) => {},
                                     Err(e) => {
-                                        send_app_log!(&self.manager_channel, Error, "Error sending message to StreamMagic Manager: {:?}", e);
+                                        send_app_log!(Error, "Error sending message to StreamMagic Manager: {:?}", e);
                                     },
                                 }
                             },
                             unexpected => {
-                                send_app_log!(&self.manager_channel, Warn, "Ignoring unexpected WebSocket message type: {:?}", unexpected);
+                                send_app_log!(Warn, "Ignoring unexpected WebSocket message type: {:?}", unexpected);
                             },
                         },
                         Err(e) => {
@@ -270,7 +261,7 @@ impl WebSocketClient {
                                 // server which has already dropped our connection (perhaps due to
                                 // our machine going to sleep).
                                 let reason = format!("WebSocketClient test failed (message read error: {})", e);
-                                send_app_log!(&self.manager_channel, Warn, "{}", reason.clone());
+                                send_app_log!(Warn, "{}", reason.clone());
                                 self.set_status(WebSocketClientStatus::Disconnected(
                                     WebSocketClientDisconnectedDetails { reason: Some(reason), consider_reconnecting: true }
                                 )).await;
@@ -278,7 +269,7 @@ impl WebSocketClient {
                                 return Err(PunyTunesError::WebSocketClientLostConnection);
                             } else {
                                 let reason = format!("Could not read next item from WebSocket server stream: {}", e);
-                                send_app_log!(&self.manager_channel, Warn, "{}", &reason);
+                                send_app_log!(Warn, "{}", &reason);
                                 self.set_status(WebSocketClientStatus::Disconnected(
                                     WebSocketClientDisconnectedDetails { reason: Some(reason.clone()), consider_reconnecting: true }
                                 )).await;
@@ -294,49 +285,50 @@ impl WebSocketClient {
 
                     if self.status == WebSocketClientStatus::TestingConnection {
                         // Check whether we haven't received a test pong response in the allotted time
-                        let now = SystemTime::now();
-
-                        if let Ok(duration) = now.duration_since(test_connection_ping_time) {
-                            if duration.as_millis() > self.test_connection_pong_timeout_ms {
-                                let reason = String::from("WebSocketClient test failed (pong timeout)");
-                                send_app_log!(&self.manager_channel, Warn, "{}", reason.clone());
-                                self.set_status(WebSocketClientStatus::Disconnected(
-                                    WebSocketClientDisconnectedDetails { reason: Some(reason), consider_reconnecting: true }
-                                )).await;
-
-                                return Err(PunyTunesError::WebSocketClientLostConnection);
-                            }
+                        if test_connection_ping_time.elapsed().as_millis() > self.test_connection_pong_timeout_ms {
+                            let reason = String::from("WebSocketClient test failed (pong timeout)");
+                            send_app_log!(Warn, "{}", reason.clone());
+                            self.set_status(WebSocketClientStatus::Disconnected(
+                                WebSocketClientDisconnectedDetails { reason: Some(reason), consider_reconnecting: true }
+                            )).await;
+
+                            return Err(PunyTunesError::WebSocketClientLostConnection);
                         }
                     }
 
-                    if self.stop_on_missing_pings {
-                        // Check if it's been too long since we last received a server ping.
-                        match SystemTime::now().duration_since(last_ping_time) {
-                            Ok(duration) => {
-                                let ping_duration_check = match ping_avg.len() {
-                                    len if len > 2 => ping_avg.average() * PING_DURATION_BUFFER_FACTOR,
-                                    _ => START_PING_THRESHOLD_SECS as f64,
-                                };
+                    // Check whether any keepalive pings have gone without a pong for too long
+                    if keepalive.check_lost(Instant::now()) {
+                        self.send_connection_quality(keepalive.quality()).await;
 
-                                if duration.as_secs() as f64 > ping_duration_check {
-                                    send_app_log!(&self.manager_channel, Warn, "WebSocket ping not received for {ping_duration_check} secs; connection lost");
+                        let lost_count = keepalive.consecutive_pings_lost();
 
-                                    return Err(PunyTunesError::WebSocketClientLostConnection);
-                                }
-                            },
-                            Err(e) => {
-                                let error = format!("Could not determine last WebSocket ping duration: {:?}", e);
-                                send_app_log!(&self.manager_channel, Error, "{}", &error);
+                        if lost_count >= MAX_CONSECUTIVE_PINGS_LOST {
+                            let reason = format!("WebSocket keepalive pong not received for {lost_count} pings; connection lost");
+                            send_app_log!(Warn, "{}", reason.clone());
+                            self.set_status(WebSocketClientStatus::Disconnected(
+                                WebSocketClientDisconnectedDetails { reason: Some(reason), consider_reconnecting: true }
+                            )).await;
 
-                                return Err(PunyTunesError::WebSocket(error));
-                            }
+                            return Err(PunyTunesError::WebSocketClientLostConnection);
+                        } else {
+                            send_app_log!(Warn, "WebSocket keepalive pong not received ({lost_count} consecutive)");
+                        }
+                    }
+                },
+
+                _ = keepalive_interval.tick() => {
+                    if let WebSocketClientStatus::Connected(_) = self.status {
+                        let payload = keepalive.ping(Instant::now());
+
+                        if let Err(e) = ws_write.send(tungstenite::Message::Ping(payload)).await {
+                            send_app_log!(Warn, "WebSocket keepalive ping error: {:?}", e);
                         }
                     }
                 },
             }
         }
 
-        send_app_log!(&self.manager_channel, Info, "WebSocketClient has stopped");
+        send_app_log!(Info, "WebSocketClient has stopped");
 
         Ok(())
     }
@@ -350,7 +342,7 @@ impl WebSocketClient {
         ),
         PunyTunesError,
     > {
-        send_app_log!(&self.manager_channel, Info, "WebSocketClient connecting to {}", &self.url);
+        send_app_log!(Info, "WebSocketClient connecting to {}", &self.url);
         self.set_status(WebSocketClientStatus::Connecting(self.url.clone())).await;
 
         // Create a WebSocket connection Request from the given URL.
@@ -406,18 +398,24 @@ impl WebSocketClient {
                 };
 
                 let reported_error = format!("WebSocketClient connection error: {:?}", error_message);
-                send_app_log!(&self.manager_channel, Warn, "{}", &reported_error);
+                send_app_log!(Warn, "{}", &reported_error);
 
                 Err(PunyTunesError::WebSocket(reported_error))
             }
             Err(_) => {
-                send_app_log!(&self.manager_channel, Warn, "WebSocketClient connection timed out");
+                send_app_log!(Warn, "WebSocketClient connection timed out");
 
                 Err(PunyTunesError::WebSocketTimeout)
             },
         };
     }
 
+    async fn send_connection_quality(&self, quality: ConnectionQuality) {
+        if let Err(e) = self.tx_channel.send(WSClientTxChannelMsg::ConnectionQualityMsg(quality)).await {
+            send_app_log!(Warn, "Could not send ConnectionQuality update: {:?}", e);
+        }
+    }
+
     async fn set_status(&mut self, status: WebSocketClientStatus) {
         self.status = status;
 
@@ -428,7 +426,7 @@ impl WebSocketClient {
         {
             Ok(()) => {}
             Err(e) => {
-                send_app_log!(&self.manager_channel, Warn, "Could not send WebSocketClientStatus update: {:?}", e);
+                send_app_log!(Warn, "Could not send WebSocketClientStatus update: {:?}", e);
             }
         }
     }
@@ -0,0 +1,188 @@
+//! Keeps the StreamMagicManager and the AmplifierManager running.
+//!
+//! The managers are expected to run for the duration of the application. If a manager ends
+//! unexpectedly (by returning from `run()`, or by panicking) then the supervisor starts a new
+//! instance of it, after a back-off delay which grows with repeated restarts.
+//!
+//! The rest of the application never talks to a manager directly. Instead, it sends messages to
+//! the supervisor's channel (the sender stored in Tauri's state, and cloned wherever needed), and
+//! the supervisor forwards them to the current manager instance. Each manager instance gets a new
+//! channel, so a restart doesn't require anyone to pick up a new sender.
+
+use std::future::Future;
+use std::time::Instant;
+
+use log::Level::{Error, Info, Warn};
+use tauri::AppHandle;
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use ts_rs::TS;
+
+use crate::amplifier_manager::{AmplifierManagerAction, AmplifierManagerChannelMsg};
+use crate::errors::PunyTunesError;
+use crate::messaging::AppMessageType;
+use crate::reconnect_policy::{ReconnectPolicy, ReconnectPolicyConfig};
+use crate::shutdown::is_shutting_down;
+use crate::streammagic_manager::{StreamMagicManagerAction, StreamMagicManagerChannelMsg};
+use crate::traits::CustomEmitters;
+
+// Size of the channel between the supervisor and each manager instance
+const MANAGER_CHANNEL_SIZE: usize = 32;
+
+// Restart back-off. The first restart is immediate, and later restarts are delayed by up to
+// 30 seconds. Restarts are never given up on.
+const RESTART_POLICY: ReconnectPolicyConfig = ReconnectPolicyConfig {
+    base_delay: Duration::from_secs(1),
+    max_delay: Duration::from_secs(30),
+    jitter: 0.2,
+    max_fast_attempts: usize::MAX,
+    background_interval: Duration::from_secs(30),
+};
+
+// A manager which ran for at least this long before ending is considered to have been stable, so
+// its restart back-off starts again from scratch
+const STABLE_RUN_DURATION: Duration = Duration::from_secs(60);
+
+/// Supervisor details which might be of interest to the UI.
+#[derive(Clone, Debug, serde::Serialize, TS)]
+#[ts(export, export_to = "../src/types/generated/SupervisorState.ts")]
+pub struct SupervisorState {
+    // Name of the supervised manager (e.g. "StreamMagicManager")
+    pub manager: String,
+    // Number of times the manager has been restarted
+    pub restart_count: usize,
+    pub is_restarting: bool,
+    // Why the manager most recently ended
+    pub last_failure: Option<String>,
+}
+
+/// Channel messages which the supervisor needs to know about.
+pub trait SupervisedChannelMsg: Send + 'static {
+    /// The message which tells a manager that the UI is ready.
+    fn ui_ready() -> Self;
+
+    fn is_ui_ready(&self) -> bool;
+}
+
+impl SupervisedChannelMsg for StreamMagicManagerChannelMsg {
+    fn ui_ready() -> Self {
+        StreamMagicManagerChannelMsg::StreamMagicManagerActionMsg(StreamMagicManagerAction::OnUIReady)
+    }
+
+    fn is_ui_ready(&self) -> bool {
+        matches!(
+            self,
+            StreamMagicManagerChannelMsg::StreamMagicManagerActionMsg(StreamMagicManagerAction::OnUIReady)
+        )
+    }
+}
+
+impl SupervisedChannelMsg for AmplifierManagerChannelMsg {
+    fn ui_ready() -> Self {
+        AmplifierManagerChannelMsg::AmplifierManagerActionMsg(AmplifierManagerAction::OnUIReady)
+    }
+
+    fn is_ui_ready(&self) -> bool {
+        matches!(
+            self,
+            AmplifierManagerChannelMsg::AmplifierManagerActionMsg(AmplifierManagerAction::OnUIReady)
+        )
+    }
+}
+
+/// Run a manager, restarting it whenever it ends (unless the application is shutting down).
+///
+/// `rx_channel` receives the messages intended for the manager. `start_manager` is called to start
+/// each new manager instance, and is given the instance's own channel to receive messages from.
+pub async fn supervise<M, F, Fut>(
+    name: &'static str,
+    app_handle: AppHandle,
+    mut rx_channel: mpsc::Receiver<M>,
+    mut start_manager: F,
+) where
+    M: SupervisedChannelMsg,
+    F: FnMut(mpsc::Receiver<M>) -> Fut + Send,
+    Fut: Future<Output = Result<(), PunyTunesError>> + Send + 'static,
+{
+    let mut state = SupervisorState {
+        manager: name.to_string(),
+        restart_count: 0,
+        is_restarting: false,
+        last_failure: None,
+    };
+    let mut restart_policy = ReconnectPolicy::new(RESTART_POLICY);
+    let mut is_ui_ready = false;
+
+    loop {
+        let (manager_tx, manager_rx) = mpsc::channel(MANAGER_CHANNEL_SIZE);
+        let mut manager_task = tauri::async_runtime::spawn(start_manager(manager_rx));
+        let started_at = Instant::now();
+
+        // A restarted manager needs to be told that the UI is ready, as the UI won't say so again
+        if is_ui_ready {
+            let _ = manager_tx.send(M::ui_ready()).await;
+        }
+
+        // Forward messages to the manager until it ends
+        let failure = loop {
+            select! {
+                msg = rx_channel.recv() => {
+                    let Some(msg) = msg else {
+                        // Nothing can send messages to the manager anymore, so it can't be restarted
+                        return;
+                    };
+
+                    if msg.is_ui_ready() {
+                        is_ui_ready = true;
+                        emit_supervisor_state(&app_handle, &state).await;
+                    }
+
+                    // If this fails then the manager has ended, which will be picked up next
+                    let _ = manager_tx.send(msg).await;
+                },
+
+                result = &mut manager_task => {
+                    break match result {
+                        Ok(Ok(())) => format!("{name} has ended"),
+                        Ok(Err(e)) => format!("{name} has ended with error: {:?}", e),
+                        Err(e) => format!("{name} has crashed: {:?}", e),
+                    };
+                },
+            }
+        };
+
+        if is_shutting_down() {
+            send_app_log!(Info, "{failure} (application is shutting down)");
+            return;
+        }
+
+        if started_at.elapsed() >= STABLE_RUN_DURATION {
+            restart_policy.reset();
+        }
+
+        let delay = restart_policy.schedule_next(Instant::now());
+
+        send_app_log!(Error, "{failure}; restarting in {}ms", delay.as_millis());
+
+        state.is_restarting = true;
+        state.last_failure = Some(failure);
+        emit_supervisor_state(&app_handle, &state).await;
+
+        sleep(delay).await;
+
+        if is_shutting_down() {
+            return;
+        }
+
+        state.restart_count += 1;
+        state.is_restarting = false;
+        emit_supervisor_state(&app_handle, &state).await;
+
+        send_app_log!(Warn, "Restarting {name} (restart #{})", state.restart_count);
+    }
+}
+
+async fn emit_supervisor_state(app_handle: &AppHandle, state: &SupervisorState) {
+    app_handle.emit_app_message(AppMessageType::SupervisorState, state).await;
+}
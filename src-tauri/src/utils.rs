@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use url::Url;
 
 pub fn host_from_url(url: &str) -> Option<String> {
@@ -7,3 +9,11 @@ pub fn host_from_url(url: &str) -> Option<String> {
 
     None
 }
+
+/// Milliseconds since the Unix epoch, for timestamps sent to the UI.
+pub fn epoch_millis_now() -> u128 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_millis(),
+        Err(_) => 0, // This really shouldn't happen
+    }
+}
@@ -3,6 +3,10 @@ import { invoke } from "@tauri-apps/api/tauri";
 import type { AmplifierAction } from "../types/generated/AmplifierAction.ts";
 import type { StreamMagicDevice } from "../types/generated/StreamMagicDevice.ts";
 import type { StreamerAction } from "../types/generated/StreamerAction.ts";
+import type { ControlBusMode } from "../types/generated/streammagic_payloads/ControlBusMode.ts";
+import type { DisplayBrightness } from "../types/generated/streammagic_payloads/DisplayBrightness.ts";
+import type { QueueAddPosition } from "../types/generated/streammagic_payloads/QueueAddPosition.ts";
+import type { SystemUpdateAction } from "../types/generated/streammagic_payloads/SystemUpdateAction.ts";
 import type { TransportToggleState } from "../types/generated/streammagic_payloads/TransportToggleState.ts";
 import { isConnectionTestPending } from "./state.ts";
 
@@ -37,6 +41,11 @@ export const sendStreamerAction = async (action: StreamerAction) => {
     await invoke("send_streamer_action", { action });
 }
 
+// Perform a streamer action in a specific zone (rather than the streamer's default zone)
+export const sendStreamerActionInZone = async (zone: string, action: StreamerAction) => {
+    await invoke("send_streamer_action", { action: { "InZone": { zone, action } } });
+}
+
 export const shutdown = async () => {
     await invoke("shutdown");
 }
@@ -81,6 +90,10 @@ export const play = async() => {
     await sendStreamerAction("Play");
 }
 
+export const playAirableRadio = async(airableRadioId: number) => {
+    await sendStreamerAction({ "PlayAirableRadio": airableRadioId });
+}
+
 export const playQueueId = async(queueId: number) => {
     await sendStreamerAction({ "PlayQueueId": queueId });
 }
@@ -89,6 +102,10 @@ export const playPresetId = async(presetId: number) => {
     await sendStreamerAction({ "PlayPresetId": presetId });
 }
 
+export const playRadioUrl = async(url: string, name: string, artUrl: string | null = null) => {
+    await sendStreamerAction({ "PlayRadioUrl": { url, name, art_url: artUrl } });
+}
+
 export const powerOn = async() => {
     await sendStreamerAction("PowerOn");
 }
@@ -101,14 +118,66 @@ export const powerToggle = async() => {
     await sendStreamerAction("PowerToggle");
 }
 
+export const presetClear = async(presetId: number) => {
+    await sendStreamerAction({ "PresetClear": presetId });
+}
+
+export const presetRename = async(presetId: number, name: string) => {
+    await sendStreamerAction({ "PresetRename": { id: presetId, name } });
+}
+
+export const presetStore = async(presetId: number) => {
+    await sendStreamerAction({ "PresetStore": presetId });
+}
+
 export const previousTrack = async() => {
     await sendStreamerAction("PreviousTrack");
 }
 
+export const queueAdd = async(url: string, title: string | null, position: QueueAddPosition) => {
+    await sendStreamerAction({ "QueueAdd": { url, title, position } });
+}
+
+export const queueClear = async() => {
+    await sendStreamerAction("QueueClear");
+}
+
+export const queueMove = async(queueId: number, position: number) => {
+    await sendStreamerAction({ "QueueMove": { id: queueId, position } });
+}
+
+export const queueRemoveIds = async(queueIds: number[]) => {
+    await sendStreamerAction({ "QueueRemoveIds": queueIds });
+}
+
 export const seek = async(position: number) => {
     await sendStreamerAction({ "Seek": position });
 }
 
+export const seekPercent = async(percent: number) => {
+    await sendStreamerAction({ "SeekPercent": percent });
+}
+
+export const seekRelative = async(offset: number) => {
+    await sendStreamerAction({ "SeekRelative": offset });
+}
+
+export const setBalance = async(balance: number) => {
+    await sendStreamerAction({ "SetBalance": balance });
+}
+
+export const setControlBusMode = async(mode: ControlBusMode) => {
+    await sendStreamerAction({ "SetControlBusMode": mode });
+}
+
+export const setDisplayBrightness = async(brightness: DisplayBrightness) => {
+    await sendStreamerAction({ "SetDisplayBrightness": brightness });
+}
+
+export const setPreAmpMode = async(isEnabled: boolean) => {
+    await sendStreamerAction({ "SetPreAmpMode": isEnabled });
+}
+
 export const setRepeatState = async(state: TransportToggleState) => {
     await sendStreamerAction({ "SetRepeat": state });
 }
@@ -121,10 +190,42 @@ export const setSourceId = async(sourceId: string) => {
     await sendStreamerAction({ "SetSourceId": sourceId });
 }
 
+export const setToneBass = async(bass: number) => {
+    await sendStreamerAction({ "SetToneBass": bass });
+}
+
+export const setToneTreble = async(treble: number) => {
+    await sendStreamerAction({ "SetToneTreble": treble });
+}
+
+export const setUserEqBandGain = async(index: number, gain: number) => {
+    await sendStreamerAction({ "SetUserEqBandGain": { index, gain } });
+}
+
+export const setUserEqEnabled = async(isEnabled: boolean) => {
+    await sendStreamerAction({ "SetUserEqEnabled": isEnabled });
+}
+
+export const sourceRename = async(sourceId: string, name: string) => {
+    await sendStreamerAction({ "SourceRename": { id: sourceId, name } });
+}
+
+export const sourceSetVisible = async(sourceId: string, isVisible: boolean) => {
+    await sendStreamerAction({ "SourceSetVisible": { id: sourceId, is_visible: isVisible } });
+}
+
+export const sourcesReorder = async(sourceIds: string[]) => {
+    await sendStreamerAction({ "SourcesReorder": sourceIds });
+}
+
 export const stop = async() => {
     await sendStreamerAction("Stop");
 }
 
+export const systemUpdate = async(action: SystemUpdateAction) => {
+    await sendStreamerAction({ "SystemUpdate": action });
+}
+
 export const togglePlayback = async() => {
     await sendStreamerAction("TogglePlayback");
 }
@@ -2,6 +2,7 @@ import { listen } from "@tauri-apps/api/event";
 
 import { appLogs, DEV_MODE, isAppOpen, isRepeatEnabled, isShuffleEnabled } from "./state.ts";
 import type { AppLog } from "../types/generated/AppLog.ts";
+import type { StreamerActionResult } from "../types/generated/StreamerActionResult.ts";
 
 /**
  * PubSub manager.
@@ -48,6 +49,7 @@ export type Events = {
     ScrollToCurrentPresetItem: undefined;
     ScrollToCurrentQueueItem: undefined;
     ShuffleModeSet: boolean;
+    StreamerActionFailed: StreamerActionResult;
     TrayAppClosed: undefined;
     TrayAppOpened: undefined;
     TrayLeftClick: undefined;
@@ -80,6 +82,10 @@ await listen("tray-left-click", () => pubSub.publish("TrayLeftClick"));
 
 await listen("app-lost-focus", () => pubSub.publish("AppLostFocus"));
 
+await listen<StreamerActionResult>("StreamerActionResult", (message) =>
+    pubSub.publish("StreamerActionFailed", message.payload),
+);
+
 pubSub.subscribe("TrayAppOpened", () => isAppOpen.set(true));
 pubSub.subscribe("TrayAppClosed", () => isAppOpen.set(false));
 
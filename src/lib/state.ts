@@ -11,12 +11,17 @@ import createThemeManager from "./themeManager.ts";
 
 // NOTE: The types with the "Streamer" prefix are the top-level types. They wrap the sub-type
 //  inside a "data" key. The wrapper exists to allow for the "zone" key to also exist when the
-//  payload data is a zone-related payload. For now, the zone is ignored and just the sub-type
-//  is exposed to the rest of the app.
+//  payload data is a zone-related payload. Zone payloads are only exposed (as the sub-type) for
+//  the active zone.
 import type { AppLog } from "../types/generated/AppLog.ts";
+import type { ConnectionQuality } from "../types/generated/ConnectionQuality.ts";
+import type { ConnectionState } from "../types/generated/ConnectionState.ts";
 import type { Level } from "../types/generated/Level.ts";
 import type { StreamMagicManagerStateMsg } from "../types/generated/StreamMagicManagerStateMsg.ts";
 import type { StreamMagicDevice } from "../types/generated/StreamMagicDevice.ts";
+import type { StreamerCapabilities } from "../types/generated/StreamerCapabilities.ts";
+import type { SupervisorState } from "../types/generated/SupervisorState.ts";
+import type { UnhandledPayload } from "../types/generated/UnhandledPayload.ts";
 import type { WebSocketClientStatus } from "../types/generated/WebSocketClientStatus.ts";
 
 import type { StreamerPresets } from "../types/generated/streammagic_payloads/StreamerPresets.ts";
@@ -37,8 +42,14 @@ import type { ZoneState } from "../types/generated/streammagic_payloads/ZoneStat
 
 import type { StreamerSystemSources } from "../types/generated/streammagic_payloads/StreamerSystemSources.ts";
 import type { StreamerZoneState } from "../types/generated/streammagic_payloads/StreamerZoneState.ts";
+import type { StreamerZoneAudio } from "../types/generated/streammagic_payloads/StreamerZoneAudio.ts";
+import type { ZoneAudio } from "../types/generated/streammagic_payloads/ZoneAudio.ts";
 import type { SystemInfo } from "../types/generated/streammagic_payloads/SystemInfo.ts";
 import type { StreamerSystemInfo } from "../types/generated/streammagic_payloads/StreamerSystemInfo.ts";
+import type { StreamerSystemDisplay } from "../types/generated/streammagic_payloads/StreamerSystemDisplay.ts";
+import type { SystemDisplay } from "../types/generated/streammagic_payloads/SystemDisplay.ts";
+import type { StreamerSystemUpdate } from "../types/generated/streammagic_payloads/StreamerSystemUpdate.ts";
+import type { SystemUpdate } from "../types/generated/streammagic_payloads/SystemUpdate.ts";
 
 import type { AmplifierManagerStateMsg } from "../types/generated/AmplifierManagerStateMsg.ts";
 import type { AmplifierState } from "../types/generated/AmplifierState.ts";
@@ -199,9 +210,9 @@ type Devices = {
 export const devices = derived(streamMagicManagerState, ($streamMagicManagerState) => {
     return {
         discovered: $streamMagicManagerState?.devices || [],
-        is_activating: $streamMagicManagerState?.is_activating ?? false,
+        is_activating: ["Activating", "Reconnecting"].includes($streamMagicManagerState?.connection_state.state ?? ""),
         is_discovering: $streamMagicManagerState?.is_discovering ?? false,
-        is_testing_connection: $streamMagicManagerState?.is_testing_connection ?? false,
+        is_testing_connection: $streamMagicManagerState?.connection_state.state === "Testing",
     } as Devices;
 });
 
@@ -216,19 +227,46 @@ export const webSocketClientStatus = derived(
 
 // ------------------------------------------------------------------------------------------------
 // Store the various StreamMagic-specific payloads. These have been stripped of their
-// WithZone/WithoutZone details as the UI components currently don't care about zones.
+// WithZone/WithoutZone details. Zone payloads are only stored for the active zone.
 
 export const resetAllStreamerState = () => {
+    activeZone.set(undefined);
     nowPlaying.set(undefined);
     playState.set(undefined);
     positionInternal.set(undefined);
     presets.set(undefined);
     queueList.set(undefined);
+    streamerCapabilities.set(undefined);
+    systemDisplay.set(undefined);
     systemPowerInternal.set(undefined);
     systemSources.set(undefined);
+    systemUpdate.set(undefined);
+    zoneAudio.set(undefined);
     zoneState.set(undefined);
 };
 
+// The zone the UI is displaying. Defaults to the first zone the streamer reports.
+export let activeZone = writable<string | undefined>();
+
+/**
+ * Whether a zone payload is for the active zone. The first zone seen becomes the active zone if
+ * there isn't one already.
+ */
+const isActiveZone = (zone: string | undefined): boolean => {
+    if (zone === undefined) {
+        return true;
+    }
+
+    const currentZone = get(activeZone);
+
+    if (currentZone === undefined) {
+        activeZone.set(zone);
+        return true;
+    }
+
+    return currentZone === zone;
+};
+
 export let nowPlaying = writable<ZoneNowPlaying | undefined>();
 
 export let playState = writable<ZonePlayState | undefined>();
@@ -243,6 +281,14 @@ export let presets = writable<Presets | undefined>();
 
 export let queueList = writable<QueueList | undefined>();
 
+// What the streamer can currently do (derived by Rust from SystemInfo and the NowPlaying controls)
+export let streamerCapabilities = writable<StreamerCapabilities | undefined>();
+
+// Streamer connection health (measured by Rust using keepalive pings)
+export let streamerConnectionQuality = writable<ConnectionQuality | undefined>();
+
+export let systemDisplay = writable<SystemDisplay | undefined>();
+
 export let systemInfo = writable<SystemInfo | undefined>();
 
 // SystemPower and ZonePosition are exposed to UI components in a simplified derived form.
@@ -254,6 +300,14 @@ export const power = derived(
 
 export const systemSources = writable<SystemSources | undefined>();
 
+export let systemUpdate = writable<SystemUpdate | undefined>();
+
+// Streamer data which the Rust side doesn't know how to handle (unhandled message paths, and
+// payload fields which aren't captured by the typed payloads). Retained to aid development.
+export let unhandledPayloads = writable<UnhandledPayload[]>([]);
+
+export let zoneAudio = writable<ZoneAudio | undefined>();
+
 export let zoneState = writable<ZoneState | undefined>();
 
 /**
@@ -282,10 +336,41 @@ export let amplifierManagerState = writable<AmplifierManagerStateMsg | undefined
 
 export let amplifierState = writable<AmplifierState | undefined>();
 
+// ------------------------------------------------------------------------------------------------
+// Store the supervisor state from Rust (one per supervised manager, keyed by manager name).
+
+export let supervisorStates = writable<Record<string, SupervisorState>>({});
+
 // ------------------------------------------------------------------------------------------------
 // Derived state helpers
 
-export const isActivating = derived(streamMagicManagerState, ($streamMagicManagerState) => !!$streamMagicManagerState?.is_activating);
+export const connectionState = derived(
+    streamMagicManagerState,
+    ($streamMagicManagerState) => $streamMagicManagerState?.connection_state ?? ({ state: "Idle" } as ConnectionState),
+);
+
+export const streamerReconnectState = derived(
+    streamMagicManagerState,
+    ($streamMagicManagerState) => $streamMagicManagerState?.reconnect,
+);
+
+export const amplifierReconnectState = derived(
+    amplifierManagerState,
+    ($amplifierManagerState) => $amplifierManagerState?.reconnect,
+);
+
+// Background reconnection attempts (made once the quick attempts have been exhausted) can go on
+// indefinitely, so they're not considered to be activating.
+export const isActivating = derived(
+    [connectionState, streamerReconnectState],
+    ([$connectionState, $streamerReconnectState]) =>
+        ["Activating", "Reconnecting"].includes($connectionState.state) && !$streamerReconnectState?.is_background,
+);
+
+// Whether any part of the backend has had to be restarted (e.g. after a crash)
+export const isBackendRestarted = derived(supervisorStates, ($supervisorStates) =>
+    Object.values($supervisorStates).some((state) => state.restart_count > 0 || state.is_restarting),
+);
 
 export const isCbusAmpModeEnabled = derived(zoneState, ($zoneState) => !!($zoneState?.cbus && ["amplifier", "receiver"].includes($zoneState.cbus)));
 
@@ -308,14 +393,11 @@ export const isAmplifierPowerOn = derived(amplifierState, ($amplifierState) => !
 //
 // Approach: Track test pending state in isConnectionTestPending. When the app is opened,
 // immediately enter test-pending state. Remain in test-pending state until the WebSocketClient's
-// "is_testing_connection" changes back to false. Do not update isConnected while test is pending.
+// connection state is no longer "Testing". Do not update isConnected while test is pending.
 
 export const isConnectionTestPending = writable<boolean>(false);
 
-const isTestingConnection = derived(
-    streamMagicManagerState,
-    ($streamMagicManagerState) => !!$streamMagicManagerState?.is_testing_connection,
-);
+const isTestingConnection = derived(connectionState, ($connectionState) => $connectionState.state === "Testing");
 
 // NOTE: The testStreamerConnection() command (commands.ts) will set isConnectionTestPending to true
 isAppOpen.subscribe((isOpen) => !isOpen && isConnectionTestPending.set(false));
@@ -407,27 +489,39 @@ const initialize = async () => {
 
         streamMagicManagerState.set(managerState);
 
-        // If we get a Disconnected state while not in activation/test mode, then we want
-        // to clear any prior streamer state so as to leave the UI in a default
-        // nothing-to-display state.
-        !managerState.is_activating &&
-            !managerState.is_testing_connection &&
+        // If we get a Disconnected state while not (re)connecting or testing (or only retrying in
+        // the background), then we want to clear any prior streamer state so as to leave the UI
+        // in a default nothing-to-display state.
+        (["Idle", "Discovering", "GivingUp"].includes(managerState.connection_state.state) ||
+            managerState.reconnect.is_background) &&
             managerState.websocket_client_status.state === "Disconnected" &&
             resetAllStreamerState();
     });
 
     // Listen for StreamMagic payload messages. When the payloads are used to set Svelte state,
-    // the WithZone/WithoutZone details are ignored and the nested payload (stored in the
-    // "data" key) is used to set the Svelte state.
+    // the WithZone/WithoutZone details are stripped and the nested payload (stored in the
+    // "data" key) is used to set the Svelte state. Zone payloads for other zones are ignored.
 
     await listen<StreamerQueueList>("StreamerQueueList", (message) => {
         queueList.set(message.payload.data);
     });
 
+    await listen<StreamerCapabilities>("StreamerCapabilities", (message) => {
+        streamerCapabilities.set(message.payload);
+    });
+
+    await listen<ConnectionQuality>("StreamerConnectionQuality", (message) => {
+        streamerConnectionQuality.set(message.payload);
+    });
+
     await listen<StreamerPresets>("StreamerPresets", (message) => {
         presets.set(message.payload.data);
     });
 
+    await listen<StreamerSystemDisplay>("StreamerSystemDisplay", (message) => {
+        systemDisplay.set(message.payload.data);
+    });
+
     await listen<StreamerSystemInfo>("StreamerSystemInfo", (message) => {
         systemInfo.set(message.payload.data);
     });
@@ -441,20 +535,52 @@ const initialize = async () => {
     });
 
     await listen<StreamerZoneNowPlaying>("StreamerZoneNowPlaying", (message) => {
+        if (!isActiveZone(message.payload.zone)) {
+            return;
+        }
+
         nowPlaying.set(message.payload.data);
     });
 
+    await listen<StreamerSystemUpdate>("StreamerSystemUpdate", (message) => {
+        systemUpdate.set(message.payload.data);
+    });
+
+    await listen<UnhandledPayload[]>("StreamerUnhandledPayloads", (message) => {
+        unhandledPayloads.set(message.payload);
+    });
+
+    await listen<StreamerZoneAudio>("StreamerZoneAudio", (message) => {
+        if (!isActiveZone(message.payload.zone)) {
+            return;
+        }
+
+        zoneAudio.set(message.payload.data);
+    });
+
     await listen<StreamerZonePlayState>("StreamerZonePlayState", (message) => {
+        if (!isActiveZone(message.payload.zone)) {
+            return;
+        }
+
         playState.set(message.payload.data);
     });
 
     await listen<StreamerZonePosition>("StreamerZonePosition", (message) => {
         // StreamerZonePosition is unique in that we allow for a missing payload (which is not
         // considered an error state).
+        if (!isActiveZone(message.payload?.zone)) {
+            return;
+        }
+
         positionInternal.set(message.payload?.data);
     });
 
     await listen<StreamerZoneState>("StreamerZoneState", (message) => {
+        if (!isActiveZone(message.payload.zone)) {
+            return;
+        }
+
         // zoneState.set(message.payload.data);
 
         // If the power state or active input has changed, then set the playhead position to
@@ -494,6 +620,11 @@ const initialize = async () => {
         console.log("AmplifierState", message.payload);
     });
 
+    await listen<SupervisorState>("SupervisorState", (message) => {
+        supervisorStates.update((states) => ({ ...states, [message.payload.manager]: message.payload }));
+        console.log("SupervisorState", message.payload);
+    });
+
     // Inform the Rust side that the UI is ready to proceed. This will trigger a full emission of
     // various state messages, allowing the Svelte state to populate.
     await uiReady();
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AmplifierDevice } from "./AmplifierDevice";
+import type { ReconnectState } from "./ReconnectState";
 
-export interface AmplifierManagerStateMsg { is_discovering: boolean, is_handling_amplifier: boolean, managed_device: AmplifierDevice | null, }
\ No newline at end of file
+export interface AmplifierManagerStateMsg { is_discovering: boolean, is_handling_amplifier: boolean, managed_device: AmplifierDevice | null, reconnect: ReconnectState, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type AppMessageType = "AmplifierManagerState" | "AmplifierState" | "AppLog" | "Devices" | "IsActivating" | "IsDiscovering" | "IsInitializingStreamMagicManager" | "StreamerSystemInfo" | "StreamerSystemPower" | "StreamerSystemSources" | "StreamerPresets" | "StreamerQueueList" | "StreamerZoneNowPlaying" | "StreamerZonePlayState" | "StreamerZonePlayStatePosition" | "StreamerZonePosition" | "StreamerZoneState" | "StreamMagicManagerState" | "StreamMagicManagerStatus" | "WebSocketClientStatus";
\ No newline at end of file
+export type AppMessageType = "AmplifierManagerState" | "AmplifierState" | "AppLog" | "Devices" | "IsActivating" | "IsDiscovering" | "IsInitializingStreamMagicManager" | "StreamerActionResult" | "StreamerCapabilities" | "StreamerConnectionQuality" | "StreamerSystemDisplay" | "StreamerSystemInfo" | "StreamerSystemPower" | "StreamerSystemSources" | "StreamerSystemUpdate" | "StreamerUnhandledPayloads" | "StreamerPresets" | "StreamerQueueList" | "StreamerZoneAudio" | "StreamerZoneNowPlaying" | "StreamerZonePlayState" | "StreamerZonePlayStatePosition" | "StreamerZonePosition" | "StreamerZoneState" | "StreamMagicManagerState" | "StreamMagicManagerStatus" | "SupervisorState" | "WebSocketClientStatus";
\ No newline at end of file
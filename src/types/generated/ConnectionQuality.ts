@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionHealth } from "./ConnectionHealth";
+
+export interface ConnectionQuality { health: ConnectionHealth, latency_avg_ms: number | null, latency_p95_ms: number | null, pings_sent: number, pings_lost: number, consecutive_pings_lost: number, }
\ No newline at end of file
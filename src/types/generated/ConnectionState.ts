@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ConnectionState = { "state": "Idle" } | { "state": "Discovering" } | { "state": "Activating", "metadata": number } | { "state": "Connected" } | { "state": "Testing" } | { "state": "Reconnecting", "metadata": number } | { "state": "GivingUp", "metadata": string };
\ No newline at end of file
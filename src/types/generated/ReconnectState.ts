@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ReconnectState { attempts: number, is_background: boolean, next_attempt_at: bigint | null, }
\ No newline at end of file
@@ -1,5 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-import type { AppLog } from "./AppLog";
 import type { StreamMagicDevice } from "./StreamMagicDevice";
 
-export type StreamMagicManagerAction = { "ActivateUdn": string } | { "ConnectToStreamer": string } | "Deactivate" | "DisconnectFromStreamer" | { "Discover": boolean } | { "EmitAppLog": AppLog } | "HandleClientError" | "OnUIReady" | { "ProcessDiscoveredDevice": StreamMagicDevice } | { "SetIsDiscovering": boolean } | "ShutDown" | { "StopWebSocketClient": boolean } | "TestConnection";
\ No newline at end of file
+export type StreamMagicManagerAction = { "ActivateUdn": string } | { "ConnectToStreamer": string } | "Deactivate" | "DisconnectFromStreamer" | { "Discover": boolean } | "HandleClientError" | "OnUIReady" | { "ProcessDiscoveredDevice": StreamMagicDevice } | { "SetIsDiscovering": boolean } | "ShutDown" | { "StopWebSocketClient": boolean } | "TestConnection";
\ No newline at end of file
@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionState } from "./ConnectionState";
+import type { ReconnectState } from "./ReconnectState";
 import type { StreamMagicDevice } from "./StreamMagicDevice";
 import type { WebSocketClientStatus } from "./WebSocketClientStatus";
 
-export interface StreamMagicManagerStateMsg { devices: Array<StreamMagicDevice>, is_activating: boolean, is_discovering: boolean, is_testing_connection: boolean, websocket_client_status: WebSocketClientStatus, }
\ No newline at end of file
+export interface StreamMagicManagerStateMsg { devices: Array<StreamMagicDevice>, connection_state: ConnectionState, is_discovering: boolean, reconnect: ReconnectState, websocket_client_status: WebSocketClientStatus, }
\ No newline at end of file
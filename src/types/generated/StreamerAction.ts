@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ControlBusMode } from "./streammagic_payloads/ControlBusMode";
+import type { DisplayBrightness } from "./streammagic_payloads/DisplayBrightness";
+import type { QueueAddPosition } from "./streammagic_payloads/QueueAddPosition";
+import type { SystemUpdateAction } from "./streammagic_payloads/SystemUpdateAction";
 import type { TransportToggleState } from "./streammagic_payloads/TransportToggleState";
 
-export type StreamerAction = { "MuteSet": boolean } | "NextTrack" | "Pause" | "Play" | { "PlayPresetId": number } | { "PlayQueueId": number } | "PowerOn" | "PowerStandby" | "PowerToggle" | "PreviousTrack" | { "Seek": number } | { "SetRepeat": TransportToggleState } | { "SetShuffle": TransportToggleState } | { "SetSourceId": string } | "Stop" | "TogglePlayback" | { "VolumePercentSet": number } | { "VolumeStepChange": number } | { "VolumeStepSet": number };
\ No newline at end of file
+export type StreamerAction = { "InZone": { zone: string, action: StreamerAction, } } | { "MuteSet": boolean } | "NextTrack" | "Pause" | "Play" | { "PlayAirableRadio": number } | { "PlayPresetId": number } | { "PlayQueueId": number } | { "PlayRadioUrl": { url: string, name: string, art_url: string | null, } } | "PowerOn" | "PowerStandby" | "PowerToggle" | { "PresetClear": number } | { "PresetRename": { id: number, name: string, } } | { "PresetStore": number } | "PreviousTrack" | { "QueueAdd": { url: string, title: string | null, position: QueueAddPosition, } } | "QueueClear" | { "QueueMove": { id: number, position: number, } } | { "QueueRemoveIds": Array<number> } | { "Seek": number } | { "SeekPercent": number } | { "SeekRelative": number } | { "SetBalance": number } | { "SetControlBusMode": ControlBusMode } | { "SetDisplayBrightness": DisplayBrightness } | { "SetPreAmpMode": boolean } | { "SetRepeat": TransportToggleState } | { "SetShuffle": TransportToggleState } | { "SetSourceId": string } | { "SetToneBass": number } | { "SetToneTreble": number } | { "SetUserEqBandGain": { index: number, gain: number, } } | { "SetUserEqEnabled": boolean } | { "SourceRename": { id: string, name: string, } } | { "SourceSetVisible": { id: string, is_visible: boolean, } } | { "SourcesReorder": Array<string> } | "Stop" | { "SystemUpdate": SystemUpdateAction } | "TogglePlayback" | { "VolumePercentSet": number } | { "VolumeStepChange": number } | { "VolumeStepSet": number };
\ No newline at end of file
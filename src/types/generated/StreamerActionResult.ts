@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamerAction } from "./StreamerAction";
+
+export interface StreamerActionResult { action: StreamerAction, result: number | null, message: string, timed_out: boolean, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StreamerCapabilities { controls_known: boolean, can_pause: boolean, can_play_next: boolean, can_play_previous: boolean, can_seek: boolean, can_stop: boolean, can_toggle_playback: boolean, can_toggle_repeat: boolean, can_toggle_shuffle: boolean, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SupervisorState { manager: string, restart_count: number, is_restarting: boolean, last_failure: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UnhandledPayloadKind } from "./UnhandledPayloadKind";
+
+export interface UnhandledPayload { path: string, kind: UnhandledPayloadKind, data: any, when: bigint, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemDisplay } from "./SystemDisplay";
+import type { WithoutZone } from "./WithoutZone";
+
+export type StreamerSystemDisplay = WithoutZone<SystemDisplay>;
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemUpdate } from "./SystemUpdate";
+import type { WithoutZone } from "./WithoutZone";
+
+export type StreamerSystemUpdate = WithoutZone<SystemUpdate>;
\ No newline at end of file
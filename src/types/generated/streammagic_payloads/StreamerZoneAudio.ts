@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WithZone } from "./WithZone";
+import type { ZoneAudio } from "./ZoneAudio";
+
+export type StreamerZoneAudio = WithZone<ZoneAudio>;
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SystemUpdate { update_available: boolean | null, updating: boolean | null, state: string | null, progress: number | null, version: string | null, }
\ No newline at end of file
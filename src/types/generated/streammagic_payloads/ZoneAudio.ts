@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ZoneAudioEqBand } from "./ZoneAudioEqBand";
+
+export interface ZoneAudio { balance: number | null, tone_bass: number | null, tone_treble: number | null, user_eq: boolean | null, user_eq_bands: Array<ZoneAudioEqBand> | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ZoneAudioEqBand { index: number | null, filter: string | null, freq: number | null, gain: number | null, q: number | null, }
\ No newline at end of file